@@ -165,12 +165,33 @@ impl LogState {
         self.namespaces.get(namespace).map(|def| &def.state)
     }
 
+    /// Gets the namespaces known to the state along with their states.
+    pub fn namespaces(&self) -> impl Iterator<Item = (&str, &NamespaceState)> {
+        self.namespaces
+            .iter()
+            .map(|(namespace, def)| (namespace.as_str(), &def.state))
+    }
+
     /// Checks the key has permission to sign checkpoints.
     pub fn key_has_permission_to_sign_checkpoints(&self, key_id: &signing::KeyID) -> bool {
         self.check_key_permissions(key_id, &[model::Permission::Commit])
             .is_ok()
     }
 
+    /// Checks the key has permission to define namespaces.
+    pub fn key_has_permission_to_define_namespaces(&self, key_id: &signing::KeyID) -> bool {
+        self.check_key_permissions(key_id, &[model::Permission::DefineNamespace])
+            .is_ok()
+    }
+
+    /// Gets the ids of the keys with permission to sign checkpoints.
+    pub fn checkpoint_signing_keys(&self) -> impl Iterator<Item = &signing::KeyID> {
+        self.permissions
+            .iter()
+            .filter(|(_, permissions)| permissions.contains(&model::Permission::Commit))
+            .map(|(key_id, _)| key_id)
+    }
+
     fn initialized(&self) -> bool {
         // The package log is initialized if the hash algorithm is set
         self.algorithm.is_some()
@@ -1,4 +1,5 @@
 use crate::{
+    datastore::DataStoreError,
     policy::{content::ContentPolicy, record::RecordPolicy},
     services::CoreService,
 };
@@ -87,6 +88,65 @@ impl From<PathRejection> for Error {
     }
 }
 
+impl From<&DataStoreError> for Error {
+    fn from(e: &DataStoreError) -> Self {
+        let status = match e {
+            DataStoreError::CheckpointNotFound(_)
+            | DataStoreError::NoCheckpoint
+            | DataStoreError::LogNotFound(_)
+            | DataStoreError::RecordNotFound(_)
+            | DataStoreError::LogLeafNotFound(_)
+            | DataStoreError::PackageNamespaceNotDefined { .. } => StatusCode::NOT_FOUND,
+            DataStoreError::Conflict
+            | DataStoreError::RecordNotPending(_)
+            | DataStoreError::RecordAlreadyCommitted { .. }
+            | DataStoreError::RecordAlreadyExists(_)
+            | DataStoreError::PackageNameMismatch { .. }
+            | DataStoreError::NonContiguousRegistryIndex { .. }
+            | DataStoreError::ContentNotValidated(_)
+            | DataStoreError::AliasConflict(_)
+            | DataStoreError::PackageNameReserved(_)
+            | DataStoreError::PackageNamespaceImported(_) => StatusCode::CONFLICT,
+            DataStoreError::KeyUnauthorized(_) | DataStoreError::UnknownKey(_) => {
+                StatusCode::UNAUTHORIZED
+            }
+            DataStoreError::PermissionDenied { .. } => StatusCode::FORBIDDEN,
+            DataStoreError::SignatureVerificationFailed(_)
+            | DataStoreError::CheckpointSignatureFailed { .. }
+            | DataStoreError::CosignatureMismatch { .. }
+            | DataStoreError::InvalidRecordContents { .. }
+            | DataStoreError::SelfReferentialRecord(_)
+            | DataStoreError::ContentSizeMismatch { .. }
+            | DataStoreError::InvalidMissingContent(_)
+            | DataStoreError::ContentNotPresent(_)
+            | DataStoreError::DuplicateCheckpoint(_)
+            | DataStoreError::TooManyMatches { .. }
+            | DataStoreError::InvalidCursor
+            | DataStoreError::InclusionIndexOutOfRange { .. }
+            | DataStoreError::InvalidConsistencyRange { .. }
+            | DataStoreError::EntryRangeOutOfBounds { .. }
+            | DataStoreError::InvalidSinceRecord(_)
+            | DataStoreError::OperatorValidationFailed(_)
+            | DataStoreError::PackageValidationFailed(_)
+            | DataStoreError::Rejection(_) => StatusCode::BAD_REQUEST,
+            DataStoreError::RecordTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            DataStoreError::StoreClosed => StatusCode::SERVICE_UNAVAILABLE,
+            DataStoreError::RevalidationFailed { .. }
+            | DataStoreError::LogStateMismatch(_)
+            | DataStoreError::ProofBundleFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            #[cfg(feature = "postgres")]
+            DataStoreError::ConnectionPool(_) => StatusCode::SERVICE_UNAVAILABLE,
+            #[cfg(feature = "postgres")]
+            DataStoreError::Diesel(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Self {
+            status,
+            message: e.to_string(),
+        }
+    }
+}
+
 pub async fn not_found() -> impl IntoResponse {
     Error {
         status: StatusCode::NOT_FOUND,
@@ -154,3 +214,224 @@ pub fn create_router(
         .nest("/verify", monitor_config.into_router())
         .fallback(not_found)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_crypto::{
+        hash::{AnyHash, Hash, Sha256},
+        signing::{generate_p256_pair, KeyID},
+    };
+    use warg_protocol::{
+        operator, package,
+        registry::{LogId, PackageName},
+    };
+
+    fn status(e: DataStoreError) -> StatusCode {
+        Error::from(&e).status
+    }
+
+    #[test]
+    fn test_data_store_error_status() {
+        let hash: AnyHash = Hash::<Sha256>::of("test").into();
+        let key_id = KeyID::from("key".to_string());
+        let (_, key) = generate_p256_pair();
+        let signature = key.sign(b"test").unwrap();
+
+        assert_eq!(
+            status(DataStoreError::CheckpointNotFound(1)),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(status(DataStoreError::NoCheckpoint), StatusCode::NOT_FOUND);
+        assert_eq!(
+            status(DataStoreError::LogNotFound(LogId::operator_log::<Sha256>())),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(DataStoreError::RecordNotFound(hash.clone().into())),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(DataStoreError::LogLeafNotFound(1)),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(DataStoreError::PackageNamespaceNotDefined {
+                namespace: "test".into(),
+                suggestions: Vec::new(),
+            }),
+            StatusCode::NOT_FOUND
+        );
+
+        assert_eq!(status(DataStoreError::Conflict), StatusCode::CONFLICT);
+        assert_eq!(
+            status(DataStoreError::RecordNotPending(hash.clone().into())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::RecordAlreadyCommitted { at: 1 }),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::RecordAlreadyExists(hash.clone().into())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::PackageNameMismatch {
+                existing: "test:a".into(),
+                got: "test:b".into(),
+            }),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::NonContiguousRegistryIndex {
+                expected: 1,
+                got: 2
+            }),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::PackageNamespaceImported("test".into())),
+            StatusCode::CONFLICT
+        );
+
+        assert_eq!(
+            status(DataStoreError::KeyUnauthorized(key_id.clone())),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(DataStoreError::UnknownKey(key_id.clone())),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(DataStoreError::PermissionDenied {
+                key_id,
+                permission: operator::Permission::Commit,
+            }),
+            StatusCode::FORBIDDEN
+        );
+
+        assert_eq!(
+            status(DataStoreError::SignatureVerificationFailed(signature)),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::CheckpointSignatureFailed { index: 1 }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::CosignatureMismatch { log_length: 1 }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::InvalidRecordContents {
+                record_id: hash.clone().into(),
+                message: "invalid".into(),
+            }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::ContentSizeMismatch {
+                digest: hash.clone(),
+                expected: 1,
+                got: 2,
+            }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::InvalidMissingContent(hash.clone())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::SelfReferentialRecord(hash.clone().into())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::ContentNotPresent(hash.clone())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::DuplicateCheckpoint(1)),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::EntryRangeOutOfBounds { from: 2, length: 1 }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::TooManyMatches {
+                prefix: "sha256:".into(),
+                limit: 50,
+            }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::ContentNotValidated(hash.clone())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::AliasConflict(
+                PackageName::new("test:alias").unwrap()
+            )),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::PackageNameReserved(
+                PackageName::new("test:reserved").unwrap()
+            )),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(DataStoreError::OperatorValidationFailed(
+                operator::ValidationError::FirstEntryIsNotInit
+            )),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::PackageValidationFailed(
+                package::ValidationError::FirstEntryIsNotInit
+            )),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::Rejection("rejected".into())),
+            StatusCode::BAD_REQUEST
+        );
+
+        assert_eq!(
+            status(DataStoreError::InvalidCursor),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::InvalidSinceRecord(hash.clone().into())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::InclusionIndexOutOfRange {
+                index: 1,
+                length: 1
+            }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::InvalidConsistencyRange { from: 2, to: 1 }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(DataStoreError::RecordTooLarge { size: 2, max: 1 }),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            status(DataStoreError::StoreClosed),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status(DataStoreError::LogStateMismatch(hash.clone().into())),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            status(DataStoreError::ProofBundleFailed("failed".into())),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
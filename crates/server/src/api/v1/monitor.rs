@@ -83,7 +83,8 @@ async fn verify_checkpoint(
             Err(error) => match error {
                 DataStoreError::UnknownKey(_)
                 | DataStoreError::SignatureVerificationFailed(_)
-                | DataStoreError::KeyUnauthorized(_) => VerificationState::Invalid,
+                | DataStoreError::KeyUnauthorized(_)
+                | DataStoreError::PermissionDenied { .. } => VerificationState::Invalid,
                 _ => return Err(MonitorApiError::from(error)),
             },
         }
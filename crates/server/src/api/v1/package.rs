@@ -144,7 +144,9 @@ impl From<DataStoreError> for PackageApiError {
             DataStoreError::UnknownKey(_) | DataStoreError::SignatureVerificationFailed(_) => {
                 PackageError::Unauthorized(e.to_string())
             }
-            DataStoreError::PackageNamespaceNotDefined(id) => PackageError::NamespaceNotDefined(id),
+            DataStoreError::PackageNamespaceNotDefined { namespace, .. } => {
+                PackageError::NamespaceNotDefined(namespace)
+            }
             DataStoreError::PackageNamespaceImported(id) => PackageError::NamespaceImported(id),
             // Other errors are internal server errors
             e => {
@@ -363,17 +365,21 @@ async fn upload_content(
     }
 
     // Only persist the file if the content was successfully processed
-    res?;
+    let size = res?;
 
     tmp_path
         .persist(config.content_path(&digest))
         .map_err(PackageApiError::internal_error)?;
 
-    // If this is the last content needed, submit the record for processing now
-    if config
-        .core_service
-        .store()
-        .set_content_present(&log_id, &record_id, &digest)
+    let store = config.core_service.store();
+    store
+        .set_content_present(&log_id, &record_id, &digest, Some(size))
+        .await?;
+
+    // The content was validated against its digest as it was processed; if
+    // this is the last content needed, submit the record for processing now
+    if store
+        .set_content_validated(&log_id, &record_id, &digest)
         .await?
     {
         config
@@ -390,13 +396,14 @@ async fn process_content(
     digest: &AnyHash,
     mut stream: BodyDataStream,
     policy: Option<&dyn ContentPolicy>,
-) -> Result<(), PackageApiError> {
+) -> Result<u64, PackageApiError> {
     let mut tmp_file = tokio::fs::File::create(&path)
         .await
         .map_err(PackageApiError::internal_error)?;
 
     let mut hasher = digest.algorithm().hasher();
     let mut policy = policy.map(|p| p.new_stream_policy(digest)).transpose()?;
+    let mut size = 0;
 
     while let Some(chunk) = stream
         .next()
@@ -409,6 +416,7 @@ async fn process_content(
         }
 
         hasher.update(&chunk);
+        size += chunk.len() as u64;
         tmp_file
             .write_all(&chunk)
             .await
@@ -426,5 +434,5 @@ async fn process_content(
         policy.finalize()?;
     }
 
-    Ok(())
+    Ok(size)
 }
@@ -1,26 +1,59 @@
-use super::{DataStore, DataStoreError};
-use futures::Stream;
+use super::{
+    CheckpointSigningDenial, DataStore, DataStoreError, DataStoreMetrics, LogKind,
+    NoopDataStoreMetrics, PageCursor, ReadOnlyDataStore, SinceBehavior, StoreStats,
+};
+use futures::{Stream, StreamExt};
 use indexmap::{IndexMap, IndexSet};
-use std::{pin::Pin, sync::Arc};
-use tokio::sync::RwLock;
-use warg_crypto::{hash::AnyHash, Encode, Signable};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    ops::Deref,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{broadcast, RwLock};
+use warg_crypto::{
+    hash::{AnyHash, Hash, HashAlgorithm, Sha256, SupportedDigest},
+    signing::KeyID,
+    Signable,
+};
 use warg_protocol::{
-    operator,
+    operator::{self, OperatorEntry},
     package::{self, PackageEntry},
     registry::{
         LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
     },
-    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
+    ProtoEnvelope, PublishedProtoEnvelope, PublishedProtoEnvelopeBody, SerdeEnvelope,
 };
+use warg_transparency::log::{LogBuilder, LogData, LogProofBundle, Node, VecLog};
 
+#[derive(Clone)]
 struct Entry<R> {
     registry_index: RegistryIndex,
     record_content: ProtoEnvelope<R>,
+    /// When the record was committed.
+    committed_at: SystemTime,
 }
 
+// As with the log's update time, the commit time is not compared.
+impl<R: PartialEq> PartialEq for Entry<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.registry_index == other.registry_index && self.record_content == other.record_content
+    }
+}
+
+impl<R: Eq> Eq for Entry<R> {}
+
+#[derive(Clone)]
 struct Log<S, R> {
     state: S,
     entries: Vec<Entry<R>>,
+    /// When an entry was last appended to the log.
+    updated_at: SystemTime,
 }
 
 impl<S, R> Default for Log<S, R>
@@ -31,10 +64,22 @@ where
         Self {
             state: S::default(),
             entries: Vec::new(),
+            updated_at: UNIX_EPOCH,
         }
     }
 }
 
+// The update time is wall-clock bookkeeping rather than log contents, so it
+// is not compared; this keeps replayed stores equal to the original.
+impl<S: PartialEq, R: PartialEq> PartialEq for Log<S, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state == other.state && self.entries == other.entries
+    }
+}
+
+impl<S: Eq, R: Eq> Eq for Log<S, R> {}
+
+#[derive(Clone, PartialEq, Eq)]
 struct Record {
     /// Index in the log's entries.
     index: usize,
@@ -42,6 +87,7 @@ struct Record {
     registry_index: RegistryIndex,
 }
 
+#[derive(Clone, PartialEq, Eq)]
 enum PendingRecord {
     Operator {
         record: Option<ProtoEnvelope<operator::OperatorRecord>>,
@@ -49,9 +95,14 @@ enum PendingRecord {
     Package {
         record: Option<ProtoEnvelope<package::PackageRecord>>,
         missing: IndexSet<AnyHash>,
+        /// Content that is present but has not yet been validated.
+        unvalidated: IndexSet<AnyHash>,
+        /// When the record was stored.
+        created_at: SystemTime,
     },
 }
 
+#[derive(Clone, PartialEq, Eq)]
 enum RejectedRecord {
     Operator {
         record: ProtoEnvelope<operator::OperatorRecord>,
@@ -63,179 +114,765 @@ enum RejectedRecord {
     },
 }
 
+/// Checks if a record has already been committed at the given registry index.
+///
+/// Committing a record again at the same index is allowed so that retried
+/// commits are idempotent; committing it at a different index is an error.
+fn is_committed_at(
+    status: &RecordStatus,
+    registry_index: RegistryIndex,
+) -> Result<bool, DataStoreError> {
+    match status {
+        RecordStatus::Validated(r) if r.registry_index == registry_index => Ok(true),
+        RecordStatus::Validated(r) => Err(DataStoreError::RecordAlreadyCommitted {
+            at: r.registry_index,
+        }),
+        _ => Ok(false),
+    }
+}
+
+/// A checkpoint along with unsigned metadata attached by the server.
+#[derive(Clone, PartialEq, Eq)]
+struct StoredCheckpoint {
+    envelope: SerdeEnvelope<TimestampedCheckpoint>,
+    metadata: IndexMap<String, String>,
+    cosignatures: Vec<SerdeEnvelope<TimestampedCheckpoint>>,
+}
+
+/// A committed leaf of the registry log.
+///
+/// The ids are reference counted so that leafs can be handed out cheaply.
+#[derive(Clone, PartialEq, Eq)]
+struct Leaf {
+    log_id: Arc<LogId>,
+    record_id: Arc<RecordId>,
+}
+
+impl Leaf {
+    fn to_log_leaf(&self) -> LogLeaf {
+        LogLeaf {
+            log_id: self.log_id.as_ref().clone(),
+            record_id: self.record_id.as_ref().clone(),
+        }
+    }
+}
+
+/// A Merkle log over the committed leafs, updated as records are committed.
+#[derive(Default, Clone)]
+struct LeafAccumulator(VecLog<Sha256, LogLeaf>);
+
+impl LeafAccumulator {
+    fn push(&mut self, leaf: &Leaf) {
+        self.0.push(&leaf.to_log_leaf());
+    }
+}
+
+impl PartialEq for LeafAccumulator {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl Eq for LeafAccumulator {}
+
+#[derive(Clone, PartialEq, Eq)]
 enum RecordStatus {
     Pending(PendingRecord),
     Rejected(RejectedRecord),
     Validated(Record),
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Eq)]
 struct State {
     operators: IndexMap<LogId, Log<operator::LogState, operator::OperatorRecord>>,
     packages: IndexMap<LogId, Log<package::LogState, package::PackageRecord>>,
     package_names: IndexMap<LogId, Option<PackageName>>,
-    checkpoints: IndexMap<RegistryLen, SerdeEnvelope<TimestampedCheckpoint>>,
+    /// Sorted by log length, so the last checkpoint is the latest.
+    checkpoints: IndexMap<RegistryLen, StoredCheckpoint>,
     records: IndexMap<LogId, IndexMap<RecordId, RecordStatus>>,
-    log_leafs: IndexMap<RegistryIndex, LogLeaf>,
+    log_leafs: IndexMap<RegistryIndex, Leaf>,
+    leaf_accumulator: LeafAccumulator,
+    /// Content sizes keyed by the record and digest of the content.
+    content_sizes: IndexMap<(LogId, RecordId, AnyHash), u64>,
+    content_progress: IndexMap<AnyHash, u64>,
+    content_to_records: IndexMap<AnyHash, IndexSet<(LogId, RecordId)>>,
+    // The operator log defining the namespace of each package log
+    governing_operators: IndexMap<LogId, LogId>,
+    // Alternative names resolving to the logs of renamed packages
+    package_aliases: IndexMap<PackageName, LogId>,
+    // Keys that package names are reserved for before their first record
+    package_reservations: IndexMap<PackageName, KeyID>,
 }
 
-/// Represents an in-memory data store.
-///
-/// Data is not persisted between restarts of the server.
-///
-/// Note: this is mainly used for testing, so it is not very efficient as
-/// it shares a single RwLock for all operations.
-pub struct MemoryDataStore(Arc<RwLock<State>>);
-
-impl MemoryDataStore {
-    pub fn new() -> Self {
-        Self(Arc::new(RwLock::new(State::default())))
+/// Ensures a record with the given id has not already been stored in the log.
+fn ensure_record_not_stored(
+    state: &State,
+    log_id: &LogId,
+    record_id: &RecordId,
+) -> Result<(), DataStoreError> {
+    if state
+        .records
+        .get(log_id)
+        .is_some_and(|records| records.contains_key(record_id))
+    {
+        return Err(DataStoreError::RecordAlreadyExists(record_id.clone()));
     }
+
+    Ok(())
 }
 
-impl Default for MemoryDataStore {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Hashes the serialized form of a package log state.
+fn log_state_hash(state: &package::LogState) -> AnyHash {
+    let bytes = serde_json::to_vec(state).expect("log state should serialize");
+    Hash::<Sha256>::of(bytes.as_slice()).into()
 }
 
-#[axum::async_trait]
-impl DataStore for MemoryDataStore {
-    async fn get_all_checkpoints(
+impl State {
+    /// Builds the verifiable log of the first `length` leaves, along with
+    /// the node of each leaf by registry index.
+    fn verifiable_log<D: SupportedDigest>(
         &self,
-    ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
-        DataStoreError,
-    > {
-        Ok(Box::pin(futures::stream::empty()))
-    }
+        length: RegistryLen,
+    ) -> (VecLog<D, LogLeaf>, IndexMap<RegistryIndex, Node>) {
+        let mut log = VecLog::default();
+        let nodes = self
+            .log_leafs
+            .iter()
+            .take(length)
+            .map(|(index, Leaf { log_id, record_id })| {
+                let node = log.push(&LogLeaf {
+                    log_id: log_id.as_ref().clone(),
+                    record_id: record_id.as_ref().clone(),
+                });
+                (*index, node)
+            })
+            .collect();
 
-    async fn get_all_validated_records(
-        &self,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
-    {
-        Ok(Box::pin(futures::stream::empty()))
+        (log, nodes)
     }
 
-    async fn get_log_leafs_starting_with_registry_index(
-        &self,
-        starting_index: RegistryIndex,
-        limit: usize,
-    ) -> Result<Vec<(RegistryIndex, LogLeaf)>, DataStoreError> {
-        let state = self.0.read().await;
-
-        let limit = if limit > state.log_leafs.len() - starting_index {
-            state.log_leafs.len() - starting_index
-        } else {
-            limit
-        };
-
-        let mut leafs = Vec::with_capacity(limit);
-        for entry in starting_index..starting_index + limit {
-            match state.log_leafs.get(&entry) {
-                Some(log_leaf) => leafs.push((entry, log_leaf.clone())),
-                None => break,
-            }
-        }
-
-        Ok(leafs)
+    /// Gets the id of the operator log that defines the given namespace.
+    fn defining_operator(&self, namespace: &str) -> Option<&LogId> {
+        self.operators.iter().find_map(|(log_id, log)| {
+            matches!(
+                log.state.namespace_state(namespace),
+                Some(operator::NamespaceState::Defined)
+            )
+            .then_some(log_id)
+        })
     }
 
-    async fn get_log_leafs_with_registry_index(
+    /// Gets the committed records of an operator log with at least one entry
+    /// matching the predicate.
+    fn operator_records_with_entry(
         &self,
-        entries: &[RegistryIndex],
-    ) -> Result<Vec<LogLeaf>, DataStoreError> {
-        let state = self.0.read().await;
-
-        let mut leafs = Vec::with_capacity(entries.len());
-        for entry in entries {
-            match state.log_leafs.get(entry) {
-                Some(log_leaf) => leafs.push(log_leaf.clone()),
-                None => return Err(DataStoreError::LogLeafNotFound(*entry)),
-            }
-        }
+        log_id: &LogId,
+        predicate: impl Fn(&OperatorEntry) -> bool,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        let log = self
+            .operators
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        Ok(leafs)
+        Ok(log
+            .entries
+            .iter()
+            .filter(|entry| entry.record_content.as_ref().entries.iter().any(&predicate))
+            .map(|entry| PublishedProtoEnvelope {
+                envelope: entry.record_content.clone(),
+                registry_index: entry.registry_index,
+            })
+            .collect())
     }
 
-    async fn get_package_names(
-        &self,
-        log_ids: &[LogId],
-    ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError> {
-        let state = self.0.read().await;
+    /// Gets the id of the first record of a log.
+    fn init_record_id(&self, log_id: &LogId) -> Result<&RecordId, DataStoreError> {
+        let records = self
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        log_ids
+        records
             .iter()
-            .map(|log_id| {
-                if let Some(opt_package_name) = state.package_names.get(log_id) {
-                    Ok((log_id.clone(), opt_package_name.clone()))
-                } else {
-                    Err(DataStoreError::LogNotFound(log_id.clone()))
-                }
+            .find(|(_, status)| matches!(status, RecordStatus::Validated(r) if r.index == 0))
+            .map(|(record_id, _)| Ok(record_id))
+            .unwrap_or_else(|| match records.keys().next() {
+                // The first stored record of the log has not been validated
+                Some(record_id) => Err(DataStoreError::RecordNotFound(record_id.clone())),
+                None => Err(DataStoreError::LogNotFound(log_id.clone())),
             })
-            .collect::<Result<IndexMap<LogId, Option<PackageName>>, _>>()
     }
 
-    async fn store_operator_record(
+    fn operator_record(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-        record: &ProtoEnvelope<operator::OperatorRecord>,
-    ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
-        let prev = state.records.entry(log_id.clone()).or_default().insert(
-            record_id.clone(),
-            RecordStatus::Pending(PendingRecord::Operator {
-                record: Some(record.clone()),
-            }),
-        );
+    ) -> Result<super::Record<operator::OperatorRecord>, DataStoreError> {
+        let status = self
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
-        assert!(prev.is_none());
-        Ok(())
+        let (status, envelope, registry_index) = match status {
+            RecordStatus::Pending(PendingRecord::Operator { record, .. }) => {
+                (super::RecordStatus::Pending, record.clone().unwrap(), None)
+            }
+            RecordStatus::Rejected(RejectedRecord::Operator { record, reason }) => (
+                super::RecordStatus::Rejected(reason.into()),
+                record.clone(),
+                None,
+            ),
+            RecordStatus::Validated(r) => {
+                let log = self
+                    .operators
+                    .get(log_id)
+                    .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+                let published_length = self
+                    .checkpoints
+                    .last()
+                    .map(|(_, c)| c.envelope.as_ref().checkpoint.log_length)
+                    .unwrap_or_default();
+
+                (
+                    if r.registry_index < published_length {
+                        super::RecordStatus::Published
+                    } else {
+                        super::RecordStatus::Validated
+                    },
+                    log.entries[r.index].record_content.clone(),
+                    Some(r.registry_index),
+                )
+            }
+            _ => return Err(DataStoreError::RecordNotFound(record_id.clone())),
+        };
+
+        Ok(super::Record {
+            status,
+            envelope,
+            registry_index,
+        })
     }
 
-    async fn reject_operator_record(
+    fn package_record(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-        reason: &str,
-    ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
-
-        let status = state
+    ) -> Result<super::Record<package::PackageRecord>, DataStoreError> {
+        let status = self
             .records
-            .get_mut(log_id)
+            .get(log_id)
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
-            .get_mut(record_id)
+            .get(record_id)
             .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
-        let record = match status {
-            RecordStatus::Pending(PendingRecord::Operator { record }) => record.take().unwrap(),
-            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
-        };
+        let (status, envelope, registry_index) = match status {
+            RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
+                (super::RecordStatus::Pending, record.clone().unwrap(), None)
+            }
+            RecordStatus::Rejected(RejectedRecord::Package { record, reason }) => (
+                super::RecordStatus::Rejected(reason.into()),
+                record.clone(),
+                None,
+            ),
+            RecordStatus::Validated(r) => {
+                let log = self
+                    .packages
+                    .get(log_id)
+                    .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        *status = RecordStatus::Rejected(RejectedRecord::Operator {
-            record,
-            reason: reason.to_string(),
-        });
+                let published_length = self
+                    .checkpoints
+                    .last()
+                    .map(|(_, c)| c.envelope.as_ref().checkpoint.log_length)
+                    .unwrap_or_default();
 
-        Ok(())
+                (
+                    if r.registry_index < published_length {
+                        super::RecordStatus::Published
+                    } else {
+                        super::RecordStatus::Validated
+                    },
+                    log.entries[r.index].record_content.clone(),
+                    Some(r.registry_index),
+                )
+            }
+            _ => return Err(DataStoreError::RecordNotFound(record_id.clone())),
+        };
+
+        Ok(super::Record {
+            status,
+            envelope,
+            registry_index,
+        })
     }
+}
 
-    async fn commit_operator_record(
-        &self,
-        log_id: &LogId,
-        record_id: &RecordId,
+/// The timestamped status transitions of a record.
+type RecordHistory = Vec<(SystemTime, String)>;
+
+/// A mutating operation performed on a [`MemoryDataStore`].
+///
+/// Operations are recorded when enabled with
+/// [`MemoryDataStore::with_record_ops`] and can be replayed into a fresh
+/// store with [`MemoryDataStore::replay_ops`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreOp {
+    StoreOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        record: ProtoEnvelope<operator::OperatorRecord>,
+    },
+    RejectOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        reason: String,
+    },
+    CommitOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
         registry_index: RegistryIndex,
-    ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
+    },
+    StorePackageRecord {
+        log_id: LogId,
+        package_name: PackageName,
+        record_id: RecordId,
+        record: ProtoEnvelope<package::PackageRecord>,
+        missing: IndexSet<AnyHash>,
+    },
+    RejectPackageRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        reason: String,
+    },
+    CommitPackageRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        registry_index: RegistryIndex,
+    },
+    SetContentPresent {
+        log_id: LogId,
+        record_id: RecordId,
+        digest: AnyHash,
+        content_size: Option<u64>,
+    },
+    SetContentValidated {
+        log_id: LogId,
+        record_id: RecordId,
+        digest: AnyHash,
+    },
+    StoreCheckpoint {
+        checkpoint_id: AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        metadata: IndexMap<String, String>,
+        cosignatures: Vec<SerdeEnvelope<TimestampedCheckpoint>>,
+    },
+}
 
-        let State {
-            operators,
-            records,
+/// Information about a rejected record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRecordInfo {
+    /// The id of the log the record belongs to.
+    pub log_id: LogId,
+    /// The id of the record.
+    pub record_id: RecordId,
+    /// The kind of log the record belongs to.
+    pub kind: LogKind,
+    /// The reason the record was rejected.
+    pub reason: String,
+}
+
+/// A committed record of either an operator or a package log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordKind {
+    /// An operator record.
+    Operator(PublishedProtoEnvelope<operator::OperatorRecord>),
+    /// A package record.
+    Package(PublishedProtoEnvelope<package::PackageRecord>),
+}
+
+/// The kind of an entry of an operator record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorEntryKind {
+    /// The entry initializes the log.
+    Init,
+    /// The entry grants permissions to a key.
+    GrantFlat,
+    /// The entry revokes permissions from a key.
+    RevokeFlat,
+    /// The entry defines a namespace.
+    DefineNamespace,
+    /// The entry imports a namespace from another registry.
+    ImportNamespace,
+}
+
+impl OperatorEntryKind {
+    fn of(entry: &OperatorEntry) -> Option<Self> {
+        match entry {
+            OperatorEntry::Init { .. } => Some(Self::Init),
+            OperatorEntry::GrantFlat { .. } => Some(Self::GrantFlat),
+            OperatorEntry::RevokeFlat { .. } => Some(Self::RevokeFlat),
+            OperatorEntry::DefineNamespace { .. } => Some(Self::DefineNamespace),
+            OperatorEntry::ImportNamespace { .. } => Some(Self::ImportNamespace),
+            _ => None,
+        }
+    }
+}
+
+/// The state of a content digest of a pending package record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentState {
+    /// The content has not been marked as present.
+    Missing,
+    /// The content is present but has not been validated.
+    Present,
+    /// The content is present and has been validated.
+    Validated,
+}
+
+/// A self-contained export of a package log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogBundle {
+    /// The id of the exported log.
+    pub log_id: LogId,
+    /// The name of the package, if known.
+    pub package_name: Option<PackageName>,
+    /// The committed records of the log, in order.
+    pub records: Vec<BundledRecord>,
+    /// The smallest checkpoint that includes the head of the log, if any.
+    pub checkpoint: Option<SerdeEnvelope<TimestampedCheckpoint>>,
+}
+
+/// A record of a [`LogBundle`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledRecord {
+    /// The id of the record.
+    pub record_id: RecordId,
+    /// The record and its registry index.
+    #[serde(flatten)]
+    pub record: PublishedProtoEnvelopeBody,
+}
+
+impl LogBundle {
+    /// Validates the bundle's records in order against a fresh log state.
+    ///
+    /// Returns the resulting log state.
+    pub fn validate(&self) -> Result<package::LogState, DataStoreError> {
+        let mut state = package::LogState::default();
+        for BundledRecord { record_id, record } in &self.records {
+            let record = PublishedProtoEnvelope::<package::PackageRecord>::try_from(record.clone())
+                .map_err(|e| DataStoreError::InvalidRecordContents {
+                    record_id: record_id.clone(),
+                    message: e.to_string(),
+                })?;
+
+            state = state.validate(&record.envelope).map_err(|source| {
+                DataStoreError::RevalidationFailed {
+                    record_id: record_id.clone(),
+                    source,
+                }
+            })?;
+        }
+
+        Ok(state)
+    }
+}
+
+/// A bounded cache of package records that evicts the least recently used.
+///
+/// Entries are never invalidated: yanking is not implemented by this store,
+/// so a cached published record cannot change. Invalidating entries on yank
+/// is left to whichever change adds yanking.
+struct RecordCache {
+    capacity: usize,
+    entries: IndexMap<(LogId, RecordId), super::Record<package::PackageRecord>>,
+}
+
+impl RecordCache {
+    fn get(&mut self, key: &(LogId, RecordId)) -> Option<super::Record<package::PackageRecord>> {
+        let index = self.entries.get_index_of(key)?;
+        self.entries.move_index(index, self.entries.len() - 1);
+        self.entries.last().map(|(_, record)| record.clone())
+    }
+
+    fn insert(&mut self, key: (LogId, RecordId), record: super::Record<package::PackageRecord>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.entries.shift_remove_index(0);
+        }
+
+        let (index, _) = self.entries.insert_full(key, record);
+        self.entries.move_index(index, self.entries.len() - 1);
+    }
+}
+
+/// A snapshot of the state of a [`MemoryDataStore`].
+///
+/// Snapshots are taken with [`MemoryDataStore::snapshot`] and applied with
+/// [`MemoryDataStore::replace_state`].
+#[derive(Clone)]
+pub struct StoreSnapshot {
+    state: State,
+}
+
+/// A read-only handle to the state of a [`MemoryDataStore`].
+///
+/// Handles are created with [`MemoryDataStore::read_handle`] and share the
+/// state and read configuration of the store, so they see all writes made
+/// through it. A handle only implements the read methods of the store.
+#[derive(Clone)]
+pub struct ReadHandle {
+    state: Arc<RwLock<State>>,
+    closed: Arc<AtomicBool>,
+    history: Arc<Mutex<IndexMap<LogId, IndexMap<RecordId, RecordHistory>>>>,
+    record_cache: Option<Arc<Mutex<RecordCache>>>,
+    max_page_limit: u16,
+}
+
+/// The number of commit times retained for each namespace.
+const PUBLISH_RATE_CAPACITY: usize = 1024;
+
+/// The maximum number of records returned when finding records by id prefix.
+const MAX_PREFIX_MATCHES: usize = 50;
+
+/// The number of content present events buffered for each subscriber.
+///
+/// Subscribers that fall further behind miss the oldest events.
+const CONTENT_PRESENT_CAPACITY: usize = 1024;
+
+/// Represents an in-memory data store.
+///
+/// Data is not persisted between restarts of the server.
+///
+/// Note: this is mainly used for testing, so it is not very efficient as
+/// it shares a single RwLock for all operations.
+///
+/// The store dereferences to a [`ReadHandle`] of its own, on which its read
+/// methods are defined.
+pub struct MemoryDataStore {
+    reader: ReadHandle,
+    metrics: Arc<dyn DataStoreMetrics>,
+    on_commit_hook: Option<Arc<dyn Fn(RegistryIndex) + Send + Sync>>,
+    ops: Option<Mutex<Vec<StoreOp>>>,
+    max_record_bytes: Option<usize>,
+    content_present: broadcast::Sender<(LogId, RecordId, AnyHash)>,
+    verify_on_store: bool,
+    publish_times: Mutex<IndexMap<String, VecDeque<Instant>>>,
+}
+
+impl Deref for MemoryDataStore {
+    type Target = ReadHandle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reader
+    }
+}
+
+impl MemoryDataStore {
+    pub fn new() -> Self {
+        Self {
+            reader: ReadHandle {
+                state: Arc::new(RwLock::new(State::default())),
+                closed: Arc::new(AtomicBool::new(false)),
+                history: Default::default(),
+                record_cache: None,
+                max_page_limit: u16::MAX,
+            },
+            metrics: Arc::new(NoopDataStoreMetrics),
+            on_commit_hook: None,
+            ops: None,
+            max_record_bytes: None,
+            content_present: broadcast::channel(CONTENT_PRESENT_CAPACITY).0,
+            verify_on_store: false,
+            publish_times: Default::default(),
+        }
+    }
+
+    /// Closes the store.
+    ///
+    /// Once closed, operations that modify the store fail with
+    /// `StoreClosed`; reads continue to succeed.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    fn ensure_open(&self) -> Result<(), DataStoreError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(DataStoreError::StoreClosed);
+        }
+
+        Ok(())
+    }
+
+    fn ensure_record_size<R>(&self, record: &ProtoEnvelope<R>) -> Result<(), DataStoreError> {
+        let size = record.content_bytes().len();
+        match self.max_record_bytes {
+            Some(max) if size > max => Err(DataStoreError::RecordTooLarge { size, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates a new store by replaying the given operations in order.
+    ///
+    /// Operations that failed when recorded fail the same way when replayed,
+    /// so the resulting state matches the store the operations came from.
+    pub fn replay_ops(ops: &[StoreOp]) -> Self {
+        let store = Self::new();
+        // The store is not shared yet, so its lock is never contended
+        futures::executor::block_on(async {
+            for op in ops {
+                store.apply_op(op).await;
+            }
+        });
+        store
+    }
+
+    /// Sets the maximum size in bytes of the encoded contents of stored records.
+    ///
+    /// Larger records are rejected with `RecordTooLarge`; by default the size is unlimited.
+    pub fn with_max_record_bytes(mut self, max_record_bytes: usize) -> Self {
+        self.max_record_bytes = Some(max_record_bytes);
+        self
+    }
+
+    /// Enables a cache of up to `capacity` package records in front of
+    /// `get_package_record`.
+    ///
+    /// Only published records are cached, as the status of pending and
+    /// validated records may still change. The cache is not invalidated on
+    /// yank, as this store does not yet support yanking records.
+    pub fn with_record_cache(mut self, capacity: usize) -> Self {
+        self.reader.record_cache = Some(Arc::new(Mutex::new(RecordCache {
+            capacity,
+            entries: IndexMap::new(),
+        })));
+        self
+    }
+
+    /// Sets whether the signatures of package records are verified when they are stored.
+    ///
+    /// When enabled, records with invalid signatures are rejected with
+    /// `SignatureVerificationFailed` before becoming pending; by default
+    /// signatures are only verified by `verify_package_record_signature`.
+    pub fn with_verify_on_store(mut self, verify_on_store: bool) -> Self {
+        self.verify_on_store = verify_on_store;
+        self
+    }
+
+    /// Sets the maximum number of records returned by a single call to
+    /// `get_operator_records`, `get_package_records` and their variants.
+    ///
+    /// Larger limits passed to those methods are clamped to this value; by
+    /// default limits are not clamped.
+    pub fn with_max_page_limit(mut self, max_page_limit: u16) -> Self {
+        self.reader.max_page_limit = max_page_limit;
+        self
+    }
+
+    /// Subscribes to content being marked as present for pending package records.
+    ///
+    /// An event is received for each digest removed from a record's missing
+    /// content. Events are never blocked on slow subscribers; a subscriber that
+    /// lags too far behind receives `RecvError::Lagged` and misses events.
+    pub fn subscribe_content_present(&self) -> broadcast::Receiver<(LogId, RecordId, AnyHash)> {
+        self.content_present.subscribe()
+    }
+
+    /// Sets whether mutating operations are recorded.
+    ///
+    /// Recorded operations are returned by [`MemoryDataStore::recorded_ops`].
+    pub fn with_record_ops(mut self, record_ops: bool) -> Self {
+        self.ops = record_ops.then(Default::default);
+        self
+    }
+
+    /// Gets the mutating operations recorded so far.
+    ///
+    /// Returns an empty list if recording is not enabled.
+    pub fn recorded_ops(&self) -> Vec<StoreOp> {
+        self.ops
+            .as_ref()
+            .map(|ops| ops.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    // Records an operation; callers hold the state lock so the recorded
+    // order matches the order in which operations were applied.
+    fn record_op(&self, op: impl FnOnce() -> StoreOp) {
+        if let Some(ops) = &self.ops {
+            ops.lock().unwrap().push(op());
+        }
+    }
+
+    // Records a status transition; callers hold the state lock.
+    fn record_transition(&self, log_id: &LogId, record_id: &RecordId, transition: String) {
+        self.history
+            .lock()
+            .unwrap()
+            .entry(log_id.clone())
+            .or_default()
+            .entry(record_id.clone())
+            .or_default()
+            .push((SystemTime::now(), transition));
+    }
+
+    fn store_operator_locked(
+        &self,
+        state: &mut State,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        ensure_record_not_stored(state, log_id, record_id)?;
+
+        self.record_op(|| StoreOp::StoreOperatorRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            record: record.clone(),
+        });
+        let prev = state.records.entry(log_id.clone()).or_default().insert(
+            record_id.clone(),
+            RecordStatus::Pending(PendingRecord::Operator {
+                record: Some(record.clone()),
+            }),
+        );
+
+        assert!(prev.is_none());
+        self.record_transition(log_id, record_id, "pending".to_string());
+        self.metrics.on_store(LogKind::Operator);
+        Ok(())
+    }
+
+    /// Commits a pending operator record.
+    ///
+    /// Returns `false` if the record was already committed at the given registry index.
+    fn commit_operator_locked(
+        &self,
+        state: &mut State,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<bool, DataStoreError> {
+        self.record_op(|| StoreOp::CommitOperatorRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            registry_index,
+        });
+
+        let State {
+            operators,
+            records,
             log_leafs,
+            leaf_accumulator,
             ..
-        } = &mut *state;
+        } = state;
 
         let status = records
             .get_mut(log_id)
@@ -243,6 +880,17 @@ impl DataStore for MemoryDataStore {
             .get_mut(record_id)
             .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
+        if is_committed_at(status, registry_index)? {
+            return Ok(false);
+        }
+
+        if registry_index != log_leafs.len() {
+            return Err(DataStoreError::NonContiguousRegistryIndex {
+                expected: log_leafs.len(),
+                got: registry_index,
+            });
+        }
+
         match status {
             RecordStatus::Pending(PendingRecord::Operator { record }) => {
                 let record = record.take().unwrap();
@@ -256,28 +904,34 @@ impl DataStore for MemoryDataStore {
                     Ok(s) => {
                         log.state = s;
                         let index = log.entries.len();
+                        let committed_at = SystemTime::now();
                         log.entries.push(Entry {
                             registry_index,
                             record_content: record,
+                            committed_at,
                         });
+                        log.updated_at = committed_at;
                         *status = RecordStatus::Validated(Record {
                             index,
                             registry_index,
                         });
-                        log_leafs.insert(
-                            registry_index,
-                            LogLeaf {
-                                log_id: log_id.clone(),
-                                record_id: record_id.clone(),
-                            },
-                        );
-                        Ok(())
+                        let leaf = Leaf {
+                            log_id: Arc::new(log_id.clone()),
+                            record_id: Arc::new(record_id.clone()),
+                        };
+                        leaf_accumulator.push(&leaf);
+                        log_leafs.insert(registry_index, leaf);
+                        self.record_transition(log_id, record_id, "validated".to_string());
+                        self.metrics.on_commit(LogKind::Operator);
+                        Ok(true)
                     }
                     Err(e) => {
                         *status = RecordStatus::Rejected(RejectedRecord::Operator {
                             record,
                             reason: e.to_string(),
                         });
+                        self.record_transition(log_id, record_id, format!("rejected: {e}"));
+                        self.metrics.on_reject(LogKind::Operator);
                         Err(e)
                     }
                 }
@@ -286,79 +940,125 @@ impl DataStore for MemoryDataStore {
         }
     }
 
-    async fn store_package_record(
-        &self,
-        log_id: &LogId,
-        package_name: &PackageName,
-        record_id: &RecordId,
-        record: &ProtoEnvelope<package::PackageRecord>,
-        missing: &IndexSet<&AnyHash>,
-    ) -> Result<(), DataStoreError> {
-        // Ensure the set of missing hashes is a subset of the record contents.
-        debug_assert!({
-            use warg_protocol::Record;
-            let contents = record.as_ref().contents();
-            missing.is_subset(&contents)
-        });
-
-        let mut state = self.0.write().await;
-        let prev = state.records.entry(log_id.clone()).or_default().insert(
-            record_id.clone(),
-            RecordStatus::Pending(PendingRecord::Package {
-                record: Some(record.clone()),
-                missing: missing.iter().map(|&d| d.clone()).collect(),
-            }),
-        );
-        state
-            .package_names
-            .insert(log_id.clone(), Some(package_name.clone()));
-
-        assert!(prev.is_none());
-        Ok(())
-    }
-
-    async fn reject_package_record(
-        &self,
-        log_id: &LogId,
-        record_id: &RecordId,
-        reason: &str,
-    ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
-
-        let status = state
-            .records
-            .get_mut(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
-            .get_mut(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
-
-        let record = match status {
-            RecordStatus::Pending(PendingRecord::Package { record, .. }) => record.take().unwrap(),
-            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+    async fn apply_op(&self, op: &StoreOp) {
+        // Errors are ignored as they were also returned when recorded
+        let _ = match op {
+            StoreOp::StoreOperatorRecord {
+                log_id,
+                record_id,
+                record,
+            } => self.store_operator_record(log_id, record_id, record).await,
+            StoreOp::RejectOperatorRecord {
+                log_id,
+                record_id,
+                reason,
+            } => self.reject_operator_record(log_id, record_id, reason).await,
+            StoreOp::CommitOperatorRecord {
+                log_id,
+                record_id,
+                registry_index,
+            } => {
+                self.commit_operator_record(log_id, record_id, *registry_index)
+                    .await
+            }
+            StoreOp::StorePackageRecord {
+                log_id,
+                package_name,
+                record_id,
+                record,
+                missing,
+            } => {
+                self.store_package_record(
+                    log_id,
+                    package_name,
+                    record_id,
+                    record,
+                    &missing.iter().collect(),
+                )
+                .await
+            }
+            StoreOp::RejectPackageRecord {
+                log_id,
+                record_id,
+                reason,
+            } => self.reject_package_record(log_id, record_id, reason).await,
+            StoreOp::CommitPackageRecord {
+                log_id,
+                record_id,
+                registry_index,
+            } => {
+                self.commit_package_record(log_id, record_id, *registry_index)
+                    .await
+            }
+            StoreOp::SetContentPresent {
+                log_id,
+                record_id,
+                digest,
+                content_size,
+            } => self
+                .set_content_present(log_id, record_id, digest, *content_size)
+                .await
+                .map(|_| ()),
+            StoreOp::SetContentValidated {
+                log_id,
+                record_id,
+                digest,
+            } => self
+                .set_content_validated(log_id, record_id, digest)
+                .await
+                .map(|_| ()),
+            StoreOp::StoreCheckpoint {
+                checkpoint_id,
+                ts_checkpoint,
+                metadata,
+                cosignatures,
+            } => {
+                self.store_checkpoint_inner(
+                    checkpoint_id,
+                    ts_checkpoint.clone(),
+                    metadata.clone(),
+                    cosignatures.clone(),
+                )
+                .await
+            }
         };
+    }
 
-        *status = RecordStatus::Rejected(RejectedRecord::Package {
-            record,
-            reason: reason.to_string(),
-        });
+    /// Sets the metrics hooks notified of record operations.
+    pub fn with_metrics(mut self, metrics: Arc<dyn DataStoreMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
 
-        Ok(())
+    /// Sets a hook invoked with the registry index of each successfully
+    /// committed record.
+    ///
+    /// The hook is called after the store's lock has been released.
+    pub fn with_on_commit_hook(mut self, hook: Arc<dyn Fn(RegistryIndex) + Send + Sync>) -> Self {
+        self.on_commit_hook = Some(hook);
+        self
     }
 
-    async fn commit_package_record(
+    fn commit_package_locked(
         &self,
+        state: &mut State,
         log_id: &LogId,
         record_id: &RecordId,
         registry_index: RegistryIndex,
-    ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
+    ) -> Result<bool, DataStoreError> {
+        self.record_op(|| StoreOp::CommitPackageRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            registry_index,
+        });
 
         let State {
             packages,
             records,
             log_leafs,
+            leaf_accumulator,
             ..
-        } = &mut *state;
+        } = state;
 
         let status = records
             .get_mut(log_id)
@@ -366,41 +1066,77 @@ impl DataStore for MemoryDataStore {
             .get_mut(record_id)
             .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
+        if is_committed_at(status, registry_index)? {
+            return Ok(false);
+        }
+
+        if registry_index != log_leafs.len() {
+            return Err(DataStoreError::NonContiguousRegistryIndex {
+                expected: log_leafs.len(),
+                got: registry_index,
+            });
+        }
+
         match status {
-            RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
+            RecordStatus::Pending(PendingRecord::Package {
+                record,
+                missing,
+                unvalidated,
+                ..
+            }) => {
+                if let Some(digest) = missing.first() {
+                    return Err(DataStoreError::ContentNotPresent(digest.clone()));
+                }
+                if let Some(digest) = unvalidated.first() {
+                    return Err(DataStoreError::ContentNotValidated(digest.clone()));
+                }
+
                 let record = record.take().unwrap();
                 let log = packages.entry(log_id.clone()).or_default();
-                match log
-                    .state
-                    .clone()
-                    .validate(&record)
-                    .map_err(DataStoreError::from)
-                {
+                // A record id is a hash over the record, `prev` included, so
+                // a genuine record can never name itself; this only guards
+                // against records stored under a fabricated id, as ids are
+                // not verified when records are stored.
+                let validated = if record.as_ref().prev.as_ref() == Some(record_id) {
+                    Err(DataStoreError::SelfReferentialRecord(record_id.clone()))
+                } else {
+                    log.state
+                        .clone()
+                        .validate(&record)
+                        .map_err(DataStoreError::from)
+                };
+                match validated {
                     Ok(state) => {
                         log.state = state;
                         let index = log.entries.len();
+                        let committed_at = SystemTime::now();
                         log.entries.push(Entry {
                             registry_index,
                             record_content: record,
+                            committed_at,
                         });
+                        log.updated_at = committed_at;
                         *status = RecordStatus::Validated(Record {
                             index,
                             registry_index,
                         });
-                        log_leafs.insert(
-                            registry_index,
-                            LogLeaf {
-                                log_id: log_id.clone(),
-                                record_id: record_id.clone(),
-                            },
-                        );
-                        Ok(())
+                        let leaf = Leaf {
+                            log_id: Arc::new(log_id.clone()),
+                            record_id: Arc::new(record_id.clone()),
+                        };
+                        leaf_accumulator.push(&leaf);
+                        log_leafs.insert(registry_index, leaf);
+                        self.record_transition(log_id, record_id, "validated".to_string());
+                        self.metrics.on_commit(LogKind::Package);
+                        Ok(true)
                     }
                     Err(e) => {
                         *status = RecordStatus::Rejected(RejectedRecord::Package {
                             record,
                             reason: e.to_string(),
                         });
+                        self.record_transition(log_id, record_id, format!("rejected: {e}"));
+                        self.metrics.on_reject(LogKind::Package);
                         Err(e)
                     }
                 }
@@ -409,246 +1145,491 @@ impl DataStore for MemoryDataStore {
         }
     }
 
-    async fn is_content_missing(
-        &self,
-        log_id: &LogId,
-        record_id: &RecordId,
-        digest: &AnyHash,
-    ) -> Result<bool, DataStoreError> {
-        let state = self.0.read().await;
-        let log = state
-            .records
-            .get(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+    fn notify_commit(&self, registry_index: RegistryIndex) {
+        if let Some(hook) = &self.on_commit_hook {
+            hook(registry_index);
+        }
+    }
 
-        let status = log
-            .get(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+    /// Rebuilds the registry log leafs, and the log accumulated over them,
+    /// from the committed records.
+    ///
+    /// Returns the number of leafs rebuilt, or `LogLeafNotFound` with the
+    /// first missing registry index if the committed records do not cover a
+    /// contiguous range; the existing leafs are left unchanged on error.
+    pub async fn rebuild_log_leafs(&self) -> Result<usize, DataStoreError> {
+        let mut state = self.state.write().await;
 
-        match status {
-            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
-                // Operator records have no content
-                Ok(false)
-            }
-            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
-                Ok(missing.contains(digest))
+        let mut leafs = state
+            .records
+            .iter()
+            .flat_map(|(log_id, records)| {
+                records
+                    .iter()
+                    .filter_map(move |(record_id, status)| match status {
+                        RecordStatus::Validated(r) => Some((
+                            r.registry_index,
+                            Leaf {
+                                log_id: Arc::new(log_id.clone()),
+                                record_id: Arc::new(record_id.clone()),
+                            },
+                        )),
+                        _ => None,
+                    })
+            })
+            .collect::<Vec<_>>();
+        leafs.sort_by_key(|(index, _)| *index);
+
+        for (expected, (index, _)) in leafs.iter().enumerate() {
+            if *index != expected {
+                return Err(DataStoreError::LogLeafNotFound(expected));
             }
-            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
         }
+
+        let mut leaf_accumulator = LeafAccumulator::default();
+        for (_, leaf) in &leafs {
+            leaf_accumulator.push(leaf);
+        }
+
+        state.leaf_accumulator = leaf_accumulator;
+        state.log_leafs = leafs.into_iter().collect();
+        Ok(state.log_leafs.len())
     }
 
-    async fn set_content_present(
+    /// Records the number of bytes received so far for a partial content upload.
+    ///
+    /// The progress is cleared once the content is marked as present.
+    pub async fn record_content_progress(
         &self,
-        log_id: &LogId,
-        record_id: &RecordId,
         digest: &AnyHash,
-    ) -> Result<bool, DataStoreError> {
-        let mut state = self.0.write().await;
-        let log = state
-            .records
-            .get_mut(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+        bytes_received: u64,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
 
-        let status = log
-            .get_mut(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+        let mut state = self.state.write().await;
+        state
+            .content_progress
+            .insert(digest.clone(), bytes_received);
+        Ok(())
+    }
 
-        match status {
-            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
-                // Operator records have no content, so conceptually already present
-                Ok(false)
-            }
-            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
-                if missing.is_empty() {
-                    return Ok(false);
-                }
+    /// Tombstones the name of the given package log.
+    ///
+    /// The log's records are kept, but `get_package_names` reports `None` for
+    /// the log until another record is stored for the package.
+    pub async fn unset_package_name(&self, log_id: &LogId) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
 
-                // Return true if this was the last missing content
-                missing.swap_remove(digest);
-                Ok(missing.is_empty())
+        let mut state = self.state.write().await;
+        let name = state
+            .package_names
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+        *name = None;
+        Ok(())
+    }
+
+    /// Reserves a package name for a key.
+    ///
+    /// Once reserved, storing an init record for the package fails with
+    /// `PackageNameReserved` unless the record's init key is the owner key.
+    /// Names without a reservation can be claimed by any key.
+    pub async fn reserve_package_name(
+        &self,
+        package_name: PackageName,
+        owner_key: KeyID,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        match state.package_reservations.get(&package_name) {
+            Some(existing) if *existing != owner_key => {
+                Err(DataStoreError::PackageNameReserved(package_name))
+            }
+            _ => {
+                state.package_reservations.insert(package_name, owner_key);
+                Ok(())
             }
-            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
         }
     }
 
-    async fn store_checkpoint(
+    /// Sets an alias that resolves to the given package log, such as the
+    /// previous name of a renamed package.
+    ///
+    /// Returns `AliasConflict` if a package log has the alias as its name.
+    pub async fn set_package_alias(
         &self,
-        _checkpoint_id: &AnyHash,
-        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        alias: PackageName,
+        target_log_id: LogId,
     ) -> Result<(), DataStoreError> {
-        let mut state = self.0.write().await;
+        self.ensure_open()?;
 
-        state
-            .checkpoints
-            .insert(ts_checkpoint.as_ref().checkpoint.log_length, ts_checkpoint);
+        let mut state = self.state.write().await;
+        if !state.packages.contains_key(&target_log_id) {
+            return Err(DataStoreError::LogNotFound(target_log_id));
+        }
+
+        if state
+            .package_names
+            .values()
+            .any(|name| name.as_ref() == Some(&alias))
+        {
+            return Err(DataStoreError::AliasConflict(alias));
+        }
 
+        state.package_aliases.insert(alias, target_log_id);
         Ok(())
     }
 
-    async fn get_latest_checkpoint(
+    /// Rejects a pending package record whose content has failed to upload.
+    ///
+    /// Returns `RecordNotPending` if the record is not a pending package record
+    /// with missing content.
+    pub async fn fail_record_content(
         &self,
-    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
-        let state = self.0.read().await;
-        let checkpoint = state.checkpoints.values().last().unwrap();
-        Ok(checkpoint.clone())
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        let status = state
+            .records
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get_mut(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        let record = match status {
+            RecordStatus::Pending(PendingRecord::Package {
+                record, missing, ..
+            }) if !missing.is_empty() => record.take().unwrap(),
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        };
+
+        // Replaying a rejection has the same effect
+        self.record_op(|| StoreOp::RejectPackageRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            reason: reason.to_string(),
+        });
+
+        *status = RecordStatus::Rejected(RejectedRecord::Package {
+            record,
+            reason: reason.to_string(),
+        });
+
+        self.record_transition(log_id, record_id, format!("rejected: {reason}"));
+        self.metrics.on_reject(LogKind::Package);
+        Ok(())
     }
 
-    async fn get_checkpoint(
+    /// Stores a checkpoint along with unsigned metadata.
+    ///
+    /// The metadata is not part of the signed checkpoint.
+    pub async fn store_checkpoint_with_metadata(
         &self,
-        log_length: RegistryLen,
-    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
-        let state = self.0.read().await;
-        let checkpoint = state
-            .checkpoints
-            .get(&log_length)
-            .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
-        Ok(checkpoint.clone())
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        metadata: IndexMap<String, String>,
+    ) -> Result<(), DataStoreError> {
+        self.store_checkpoint_inner(checkpoint_id, ts_checkpoint, metadata, Vec::new())
+            .await
     }
 
-    async fn get_operator_records(
+    /// Stores a checkpoint along with co-signatures of the same checkpoint
+    /// from other operators.
+    ///
+    /// Returns `CosignatureMismatch` if a co-signature signs a different checkpoint.
+    pub async fn store_checkpoint_with_cosignatures(
         &self,
-        log_id: &LogId,
-        registry_log_length: RegistryLen,
-        since: Option<&RecordId>,
-        limit: u16,
-    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
-        let state = self.0.read().await;
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        cosignatures: Vec<SerdeEnvelope<TimestampedCheckpoint>>,
+    ) -> Result<(), DataStoreError> {
+        self.store_checkpoint_inner(checkpoint_id, ts_checkpoint, IndexMap::new(), cosignatures)
+            .await
+    }
 
-        let log = state
-            .operators
-            .get(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+    async fn store_checkpoint_inner(
+        &self,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        metadata: IndexMap<String, String>,
+        cosignatures: Vec<SerdeEnvelope<TimestampedCheckpoint>>,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
 
-        if !state.checkpoints.contains_key(&registry_log_length) {
-            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
-        };
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+        if cosignatures
+            .iter()
+            .any(|cosignature| cosignature.as_ref().checkpoint != *checkpoint)
+        {
+            return Err(DataStoreError::CosignatureMismatch {
+                log_length: checkpoint.log_length,
+            });
+        }
 
-        let start_log_idx = match since {
-            Some(since) => {
-                match state.records.get(log_id).and_then(|records| records.get(since)) {
-                    Some(RecordStatus::Validated(record)) => record.index + 1,
-                    // If record not found or not in validated state, start from beginning
-                    _ => 0,
-                }
+        let mut state = self.state.write().await;
+        self.record_op(|| StoreOp::StoreCheckpoint {
+            checkpoint_id: checkpoint_id.clone(),
+            ts_checkpoint: ts_checkpoint.clone(),
+            metadata: metadata.clone(),
+            cosignatures: cosignatures.clone(),
+        });
+
+        state.checkpoints.insert_sorted(
+            checkpoint.log_length,
+            StoredCheckpoint {
+                envelope: ts_checkpoint,
+                metadata,
+                cosignatures,
             },
-            None => 0,
-        };
+        );
 
-        Ok(log
-            .entries
-            .iter()
-            .skip(start_log_idx)
-            .take_while(|entry| entry.registry_index < registry_log_length)
-            .map(|entry| PublishedProtoEnvelope {
-                envelope: entry.record_content.clone(),
-                registry_index: entry.registry_index,
-            })
-            .take(limit as usize)
-            .collect())
+        Ok(())
     }
 
-    async fn get_package_records(
+    /// Stores multiple checkpoints under a single write lock.
+    ///
+    /// Returns `DuplicateCheckpoint` without storing any checkpoint if two
+    /// checkpoints of the batch have the same log length.
+    pub async fn store_checkpoints_batch(
         &self,
-        log_id: &LogId,
-        registry_log_length: RegistryLen,
-        since: Option<&RecordId>,
-        limit: u16,
-    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
-        let state = self.0.read().await;
+        checkpoints: Vec<(AnyHash, SerdeEnvelope<TimestampedCheckpoint>)>,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
 
-        let log = state
-            .packages
-            .get(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+        let mut log_lengths = IndexSet::with_capacity(checkpoints.len());
+        for (_, ts_checkpoint) in &checkpoints {
+            let log_length = ts_checkpoint.as_ref().checkpoint.log_length;
+            if !log_lengths.insert(log_length) {
+                return Err(DataStoreError::DuplicateCheckpoint(log_length));
+            }
+        }
 
-        if !state.checkpoints.contains_key(&registry_log_length) {
-            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
-        };
+        let mut state = self.state.write().await;
+        for (checkpoint_id, ts_checkpoint) in checkpoints {
+            self.record_op(|| StoreOp::StoreCheckpoint {
+                checkpoint_id,
+                ts_checkpoint: ts_checkpoint.clone(),
+                metadata: IndexMap::new(),
+                cosignatures: Vec::new(),
+            });
 
-        let start_log_idx = match since {
-            Some(since) => {
-                match state.records.get(log_id).and_then(|records| records.get(since)) {
-                    Some(RecordStatus::Validated(record)) => record.index + 1,
-                    // If record not found or not in validated state, start from beginning
-                    _ => 0,
-                }
-            },
-            None => 0,
+            state.checkpoints.insert_sorted(
+                ts_checkpoint.as_ref().checkpoint.log_length,
+                StoredCheckpoint {
+                    envelope: ts_checkpoint,
+                    metadata: IndexMap::new(),
+                    cosignatures: Vec::new(),
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Records that a package record was committed to the given namespace.
+    ///
+    /// This is called when package records are committed. Only the most
+    /// recent commit times of each namespace are retained; the oldest are
+    /// evicted as new ones are recorded.
+    fn record_publish_for_rate(&self, namespace: &str) {
+        let mut publish_times = self.publish_times.lock().unwrap();
+        let times = publish_times.entry(namespace.to_string()).or_default();
+        if times.len() >= PUBLISH_RATE_CAPACITY {
+            times.pop_front();
+        }
+        times.push_back(Instant::now());
+    }
+
+    /// Gets the number of package records committed to the given namespace
+    /// within the given window.
+    pub async fn get_publish_count(
+        &self,
+        namespace: &str,
+        window: Duration,
+    ) -> Result<usize, DataStoreError> {
+        let publish_times = self.publish_times.lock().unwrap();
+        let times = match publish_times.get(namespace) {
+            Some(times) => times,
+            None => return Ok(0),
         };
 
-        Ok(log
-            .entries
+        let now = Instant::now();
+        Ok(times
             .iter()
-            .skip(start_log_idx)
-            .take_while(|entry| entry.registry_index < registry_log_length)
-            .map(|entry| PublishedProtoEnvelope {
-                envelope: entry.record_content.clone(),
-                registry_index: entry.registry_index,
-            })
-            .take(limit as usize)
-            .collect())
+            .rev()
+            .take_while(|time| now.duration_since(**time) <= window)
+            .count())
     }
 
-    async fn get_operator_record(
+    /// Commits several pending records to a package log atomically.
+    ///
+    /// The records are validated in order against the evolving log state
+    /// before any is committed; if any record fails, the error is returned
+    /// and none of the records are committed or rejected.
+    pub async fn commit_package_records_atomic(
         &self,
         log_id: &LogId,
-        record_id: &RecordId,
-    ) -> Result<super::Record<operator::OperatorRecord>, DataStoreError> {
-        let state = self.0.read().await;
-        let status = state
+        records: &[(RecordId, RegistryIndex)],
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        let pending = state
             .records
             .get(log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
-            .get(record_id)
-            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+        let mut log_state = state
+            .packages
+            .get(log_id)
+            .map(|log| log.state.clone())
+            .unwrap_or_default();
+        for (next_index, (record_id, registry_index)) in (state.log_leafs.len()..).zip(records) {
+            // Check the status first so that a committed record is reported
+            // as such rather than as being at the wrong index
+            let status = pending
+                .get(record_id)
+                .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+            if let RecordStatus::Validated(r) = status {
+                return Err(DataStoreError::RecordAlreadyCommitted {
+                    at: r.registry_index,
+                });
+            }
 
-        let (status, envelope, registry_index) = match status {
-            RecordStatus::Pending(PendingRecord::Operator { record, .. }) => {
-                (super::RecordStatus::Pending, record.clone().unwrap(), None)
+            if *registry_index != next_index {
+                return Err(DataStoreError::NonContiguousRegistryIndex {
+                    expected: next_index,
+                    got: *registry_index,
+                });
             }
-            RecordStatus::Rejected(RejectedRecord::Operator { record, reason }) => (
-                super::RecordStatus::Rejected(reason.into()),
-                record.clone(),
-                None,
-            ),
-            RecordStatus::Validated(r) => {
-                let log = state
-                    .operators
-                    .get(log_id)
-                    .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-                let published_length = state
-                    .checkpoints
-                    .last()
-                    .map(|(_, c)| c.as_ref().checkpoint.log_length)
-                    .unwrap_or_default();
+            let record = match status {
+                RecordStatus::Pending(PendingRecord::Package {
+                    record: Some(record),
+                    missing,
+                    unvalidated,
+                    ..
+                }) => {
+                    if let Some(digest) = missing.first() {
+                        return Err(DataStoreError::ContentNotPresent(digest.clone()));
+                    }
+                    if let Some(digest) = unvalidated.first() {
+                        return Err(DataStoreError::ContentNotValidated(digest.clone()));
+                    }
+                    record
+                }
+                _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+            };
 
-                (
-                    if r.registry_index < published_length {
-                        super::RecordStatus::Published
-                    } else {
-                        super::RecordStatus::Validated
-                    },
-                    log.entries[r.index].record_content.clone(),
-                    Some(r.registry_index),
-                )
+            // Only reachable for records stored under a fabricated id; see
+            // `commit_package_locked`
+            if record.as_ref().prev.as_ref() == Some(record_id) {
+                return Err(DataStoreError::SelfReferentialRecord(record_id.clone()));
             }
-            _ => return Err(DataStoreError::RecordNotFound(record_id.clone())),
-        };
+            log_state = log_state.validate(record)?;
+        }
 
-        Ok(super::Record {
-            status,
-            envelope,
-            registry_index,
-        })
+        for (record_id, registry_index) in records {
+            let committed =
+                self.commit_package_locked(&mut state, log_id, record_id, *registry_index)?;
+            debug_assert!(committed);
+        }
+
+        let package_name = state.package_names.get(log_id).cloned().flatten();
+        drop(state);
+        for (_, registry_index) in records {
+            if let Some(package_name) = &package_name {
+                self.record_publish_for_rate(package_name.namespace());
+            }
+            self.notify_commit(*registry_index);
+        }
+
+        Ok(())
     }
 
-    async fn get_package_record(
+    /// Creates a read-only handle sharing the state of the store.
+    ///
+    /// Handles are cheap to clone and cannot modify the store.
+    pub fn read_handle(&self) -> ReadHandle {
+        self.reader.clone()
+    }
+
+    /// Replaces the state of the store with the given snapshot.
+    ///
+    /// The replacement is atomic with respect to other operations on the store.
+    pub async fn replace_state(&self, snapshot: StoreSnapshot) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        *state = snapshot.state;
+
+        // Cached records may not exist in the new state
+        if let Some(cache) = &self.record_cache {
+            cache.lock().unwrap().entries.clear();
+        }
+
+        Ok(())
+    }
+}
+
+impl ReadHandle {
+    /// Checks whether the store has been closed with [`MemoryDataStore::close`].
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn page_limit(&self, limit: u16) -> usize {
+        limit.min(self.max_page_limit) as usize
+    }
+
+    /// Gets multiple package records from the same log.
+    ///
+    /// The records are returned in the order of the given record ids.
+    ///
+    /// Returns an error for the first record id that is not found.
+    pub async fn get_package_records_by_id(
+        &self,
+        log_id: &LogId,
+        record_ids: &[RecordId],
+    ) -> Result<Vec<super::Record<package::PackageRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+        record_ids
+            .iter()
+            .map(|record_id| state.package_record(log_id, record_id))
+            .collect()
+    }
+
+    /// Verifies that the given leaf is the one committed at the given
+    /// registry index.
+    ///
+    /// Returns `LogLeafNotFound` if no leaf was committed at the index.
+    pub async fn verify_log_leaf(
+        &self,
+        registry_index: RegistryIndex,
+        claimed: &LogLeaf,
+    ) -> Result<bool, DataStoreError> {
+        let state = self.state.read().await;
+        let leaf = state
+            .log_leafs
+            .get(&registry_index)
+            .ok_or(DataStoreError::LogLeafNotFound(registry_index))?;
+
+        Ok(*leaf.log_id == claimed.log_id && *leaf.record_id == claimed.record_id)
+    }
+
+    /// Gets the registry index of a record.
+    ///
+    /// Returns `None` if the record has not been validated.
+    pub async fn get_record_registry_index(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-    ) -> Result<super::Record<package::PackageRecord>, DataStoreError> {
-        let state = self.0.read().await;
+    ) -> Result<Option<RegistryIndex>, DataStoreError> {
+        let state = self.state.read().await;
         let status = state
             .records
             .get(log_id)
@@ -656,143 +1637,7436 @@ impl DataStore for MemoryDataStore {
             .get(record_id)
             .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
-        let (status, envelope, registry_index) = match status {
-            RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
-                (super::RecordStatus::Pending, record.clone().unwrap(), None)
-            }
-            RecordStatus::Rejected(RejectedRecord::Package { record, reason }) => (
-                super::RecordStatus::Rejected(reason.into()),
-                record.clone(),
-                None,
-            ),
-            RecordStatus::Validated(r) => {
-                let log = state
-                    .packages
-                    .get(log_id)
-                    .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+        match status {
+            RecordStatus::Validated(record) => Ok(Some(record.registry_index)),
+            _ => Ok(None),
+        }
+    }
 
-                let published_length = state
-                    .checkpoints
-                    .last()
-                    .map(|(_, c)| c.as_ref().checkpoint.log_length)
-                    .unwrap_or_default();
+    /// Finds the operator log that defines the given namespace.
+    ///
+    /// If multiple operator logs define the namespace, the first one stored
+    /// is returned.
+    pub async fn find_operator_for_namespace(
+        &self,
+        namespace: &str,
+    ) -> Result<Option<LogId>, DataStoreError> {
+        let state = self.state.read().await;
+        let mut defined = state.operators.iter().filter(|(_, log)| {
+            matches!(
+                log.state.namespace_state(namespace),
+                Some(operator::NamespaceState::Defined)
+            )
+        });
 
-                (
-                    if r.registry_index < published_length {
-                        super::RecordStatus::Published
-                    } else {
-                        super::RecordStatus::Validated
-                    },
-                    log.entries[r.index].record_content.clone(),
-                    Some(r.registry_index),
-                )
-            }
-            _ => return Err(DataStoreError::RecordNotFound(record_id.clone())),
-        };
+        let found = defined.next().map(|(log_id, _)| log_id.clone());
+        if defined.next().is_some() {
+            tracing::warn!(
+                "namespace `{namespace}` is defined by multiple operator logs; using `{}`",
+                found.as_ref().unwrap()
+            );
+        }
 
-        Ok(super::Record {
-            status,
-            envelope,
-            registry_index,
+        Ok(found)
+    }
+
+    /// Gets the committed records of an operator log with entries that define
+    /// or import the given namespace.
+    pub async fn get_operator_records_affecting_namespace(
+        &self,
+        operator_log_id: &LogId,
+        namespace: &str,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+        state.operator_records_with_entry(operator_log_id, |entry| match entry {
+            OperatorEntry::DefineNamespace { namespace: n }
+            | OperatorEntry::ImportNamespace { namespace: n, .. } => n == namespace,
+            _ => false,
         })
     }
 
-    async fn verify_package_record_signature(
+    /// Gets the committed records of an operator log with at least one entry
+    /// of the given kind.
+    pub async fn get_operator_records_by_entry_kind(
         &self,
         log_id: &LogId,
-        record: &ProtoEnvelope<package::PackageRecord>,
-    ) -> Result<(), DataStoreError> {
-        let state = self.0.read().await;
-        let key = match state
-            .packages
+        kind: OperatorEntryKind,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+        state
+            .operator_records_with_entry(log_id, |entry| OperatorEntryKind::of(entry) == Some(kind))
+    }
+
+    /// Gets the committed leafs starting with the given registry index.
+    ///
+    /// This is equivalent to
+    /// [`DataStore::get_log_leafs_starting_with_registry_index`] but returns
+    /// shared ids that are cheap to clone.
+    pub async fn get_leaf_refs_starting_with(
+        &self,
+        start: RegistryIndex,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, Arc<LogId>, Arc<RecordId>)>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .log_leafs
+            .iter()
+            .skip(start)
+            .take(limit)
+            .map(|(index, leaf)| (*index, leaf.log_id.clone(), leaf.record_id.clone()))
+            .collect())
+    }
+
+    /// Gets the latest checkpoint with a timestamp at or before the given time.
+    ///
+    /// Returns `None` if every checkpoint is later than the given time.
+    pub async fn get_checkpoint_at_time(
+        &self,
+        time: SystemTime,
+    ) -> Result<Option<SerdeEnvelope<TimestampedCheckpoint>>, DataStoreError> {
+        // Checkpoint timestamps are in seconds since the epoch
+        let Ok(time) = time.duration_since(UNIX_EPOCH) else {
+            return Ok(None);
+        };
+        let time = time.as_secs();
+
+        let state = self.state.read().await;
+        Ok(state
+            .checkpoints
+            .values()
+            .map(|c| &c.envelope)
+            .filter(|c| c.as_ref().timestamp <= time)
+            .max_by_key(|c| c.as_ref().timestamp)
+            .cloned())
+    }
+
+    /// Gets the pending package records of a log that are still missing
+    /// content, along with the digests of the missing content.
+    pub async fn get_records_awaiting_content(
+        &self,
+        log_id: &LogId,
+    ) -> Result<Vec<(RecordId, IndexSet<AnyHash>)>, DataStoreError> {
+        let state = self.state.read().await;
+        let records = state
+            .records
             .get(log_id)
-            .and_then(|log| log.state.public_key(record.key_id()))
-        {
-            Some(key) => Some(key),
-            None => match record.as_ref().entries.first() {
-                Some(PackageEntry::Init { key, .. }) => Some(key),
-                _ => return Err(DataStoreError::UnknownKey(record.key_id().clone())),
-            },
-        }
-        .ok_or_else(|| DataStoreError::UnknownKey(record.key_id().clone()))?;
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        package::PackageRecord::verify(key, record.content_bytes(), record.signature())
-            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+        Ok(records
+            .iter()
+            .filter_map(|(record_id, status)| match status {
+                RecordStatus::Pending(PendingRecord::Package { missing, .. })
+                    if !missing.is_empty() =>
+                {
+                    Some((record_id.clone(), missing.clone()))
+                }
+                _ => None,
+            })
+            .collect())
     }
 
-    async fn verify_can_publish_package(
+    /// Gets the registry an imported namespace is imported from.
+    ///
+    /// Returns `None` if the namespace is defined locally or not defined.
+    pub async fn get_imported_namespace_source(
         &self,
         operator_log_id: &LogId,
-        package_name: &PackageName,
-    ) -> Result<(), DataStoreError> {
-        let state = self.0.read().await;
-
-        // verify namespace is defined and not imported
+        namespace: &str,
+    ) -> Result<Option<String>, DataStoreError> {
+        let state = self.state.read().await;
         match state
             .operators
             .get(operator_log_id)
             .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?
             .state
-            .namespace_state(package_name.namespace())
+            .namespace_state(namespace)
         {
-            Some(state) => match state {
-                operator::NamespaceState::Defined => {}
-                operator::NamespaceState::Imported { .. } => {
-                    return Err(DataStoreError::PackageNamespaceImported(
-                        package_name.namespace().to_string(),
-                    ))
-                }
-            },
-            None => {
-                return Err(DataStoreError::PackageNamespaceNotDefined(
-                    package_name.namespace().to_string(),
-                ))
+            Some(operator::NamespaceState::Imported { registry }) => Ok(Some(registry.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Gets the status transitions of a record, oldest first.
+    pub async fn get_record_history(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Vec<(SystemTime, String)>, DataStoreError> {
+        // Hold the state lock so the history is consistent with the record
+        let _state = self.state.read().await;
+        self.history
+            .lock()
+            .unwrap()
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .cloned()
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))
+    }
+
+    /// Finds package names that are associated with more than one log.
+    ///
+    /// Log ids are derived from package names, so any name returned
+    /// indicates an inconsistency in the store.
+    pub async fn find_duplicate_package_names(
+        &self,
+    ) -> Result<Vec<(PackageName, Vec<LogId>)>, DataStoreError> {
+        let state = self.state.read().await;
+        let mut logs_by_name: IndexMap<&PackageName, Vec<LogId>> = IndexMap::new();
+        for (log_id, name) in &state.package_names {
+            if let Some(name) = name {
+                logs_by_name.entry(name).or_default().push(log_id.clone());
             }
         }
 
-        Ok(())
+        Ok(logs_by_name
+            .into_iter()
+            .filter(|(_, log_ids)| log_ids.len() > 1)
+            .map(|(name, log_ids)| (name.clone(), log_ids))
+            .collect())
     }
 
-    async fn verify_timestamped_checkpoint_signature(
+    /// Gets the checkpoint with the greatest log length less than the given
+    /// log length.
+    pub async fn get_checkpoint_before(
         &self,
-        operator_log_id: &LogId,
-        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
-    ) -> Result<(), DataStoreError> {
-        let state = self.0.read().await;
+        log_length: RegistryLen,
+    ) -> Result<Option<SerdeEnvelope<TimestampedCheckpoint>>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .checkpoints
+            .iter()
+            .filter(|(len, _)| **len < log_length)
+            .max_by_key(|(len, _)| **len)
+            .map(|(_, checkpoint)| checkpoint.envelope.clone()))
+    }
 
-        let state = &state
-            .operators
-            .get(operator_log_id)
-            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?
-            .state;
+    /// Gets the checkpoint with the least log length greater than the given
+    /// log length.
+    pub async fn get_checkpoint_after(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Option<SerdeEnvelope<TimestampedCheckpoint>>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .checkpoints
+            .iter()
+            .filter(|(len, _)| **len > log_length)
+            .min_by_key(|(len, _)| **len)
+            .map(|(_, checkpoint)| checkpoint.envelope.clone()))
+    }
 
-        TimestampedCheckpoint::verify(
-            state
-                .public_key(ts_checkpoint.key_id())
-                .ok_or(DataStoreError::UnknownKey(ts_checkpoint.key_id().clone()))?,
-            &ts_checkpoint.as_ref().encode(),
-            ts_checkpoint.signature(),
-        )
-        .or(Err(DataStoreError::SignatureVerificationFailed(
-            ts_checkpoint.signature().clone(),
-        )))?;
+    /// Gets a page of published records of a package log.
+    ///
+    /// Pass `None` to get the first page, then the returned cursor to get
+    /// each following page; no cursor is returned for the last page.
+    pub async fn get_package_records_paged(
+        &self,
+        log_id: &LogId,
+        cursor: Option<PageCursor>,
+        limit: u16,
+    ) -> Result<
+        (
+            Vec<PublishedProtoEnvelope<package::PackageRecord>>,
+            Option<PageCursor>,
+        ),
+        DataStoreError,
+    > {
+        let since_index = cursor.map(|c| c.registry_index()).transpose()?;
 
-        if !state.key_has_permission_to_sign_checkpoints(ts_checkpoint.key_id()) {
-            return Err(DataStoreError::KeyUnauthorized(
-                ts_checkpoint.key_id().clone(),
-            ));
+        let state = self.state.read().await;
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let published_length = state
+            .checkpoints
+            .last()
+            .map(|(_, c)| c.envelope.as_ref().checkpoint.log_length)
+            .unwrap_or_default();
+
+        // Entries are sorted by registry index, so find the first entry after the cursor
+        let start_log_idx = match since_index {
+            Some(since_index) => log
+                .entries
+                .partition_point(|entry| entry.registry_index <= since_index),
+            None => 0,
+        };
+
+        let mut published = log.entries[start_log_idx..]
+            .iter()
+            .take_while(|entry| entry.registry_index < published_length);
+        let records = published
+            .by_ref()
+            .take(self.page_limit(limit))
+            .map(|entry| PublishedProtoEnvelope {
+                envelope: entry.record_content.clone(),
+                registry_index: entry.registry_index,
+            })
+            .collect::<Vec<_>>();
+
+        let next = match (published.next(), records.last()) {
+            (Some(_), Some(last)) => Some(PageCursor::new(last.registry_index)),
+            _ => None,
+        };
+
+        Ok((records, next))
+    }
+
+    /// Gets the committed records of a package log with entry indexes in
+    /// `[from, to)`.
+    ///
+    /// `to` is clamped to the number of entries; returns
+    /// `EntryRangeOutOfBounds` if `from` is past the end of the log.
+    pub async fn get_package_entries_by_range(
+        &self,
+        log_id: &LogId,
+        from: usize,
+        to: usize,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let length = log.entries.len();
+        if from > length {
+            return Err(DataStoreError::EntryRangeOutOfBounds { from, length });
         }
 
-        Ok(())
+        Ok(log.entries[from..to.clamp(from, length)]
+            .iter()
+            .map(|entry| PublishedProtoEnvelope {
+                envelope: entry.record_content.clone(),
+                registry_index: entry.registry_index,
+            })
+            .collect())
     }
 
-    #[cfg(feature = "debug")]
-    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
-        let state = self.0.read().await;
+    /// Determines if a package log has at least one committed record.
+    ///
+    /// A log with only pending or rejected records does not exist.
+    pub async fn package_exists(&self, log_id: &LogId) -> Result<bool, DataStoreError> {
+        let state = self.state.read().await;
         Ok(state
-            .package_names
-            .values()
-            .filter_map(|opt_package_name| opt_package_name.clone())
-            .collect())
+            .packages
+            .get(log_id)
+            .map(|log| !log.entries.is_empty())
+            .unwrap_or(false))
+    }
+
+    /// Gets the number of content digests still missing for a pending record.
+    ///
+    /// Operator records have no content, so their count is always zero.
+    pub async fn missing_content_count(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<usize, DataStoreError> {
+        let state = self.state.read().await;
+        let status = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Operator { .. }) => Ok(0),
+            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => Ok(missing.len()),
+            _ => Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
+
+    /// Streams the operator records of the given log, up to the given registry log length.
+    ///
+    /// Unlike `get_operator_records`, the stream is not limited; all records after
+    /// `since` in the registry log are returned.
+    pub async fn stream_operator_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<
+                        Item = Result<
+                            PublishedProtoEnvelope<operator::OperatorRecord>,
+                            DataStoreError,
+                        >,
+                    > + Send,
+            >,
+        >,
+        DataStoreError,
+    > {
+        let state = self.state.read().await;
+
+        let log = state
+            .operators
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        if !state.checkpoints.contains_key(&registry_log_length) {
+            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
+        };
+
+        let start_log_idx = match since {
+            Some(since) => {
+                match state
+                    .records
+                    .get(log_id)
+                    .and_then(|records| records.get(since))
+                {
+                    Some(RecordStatus::Validated(record)) => record.index + 1,
+                    // If record not found or not in validated state, start from beginning
+                    _ => 0,
+                }
+            }
+            None => 0,
+        };
+
+        let records = log
+            .entries
+            .iter()
+            .skip(start_log_idx)
+            .take_while(|entry| entry.registry_index < registry_log_length)
+            .map(|entry| {
+                Ok(PublishedProtoEnvelope {
+                    envelope: entry.record_content.clone(),
+                    registry_index: entry.registry_index,
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(Box::pin(futures::stream::iter(records)))
+    }
+
+    /// Gets the number of bytes received so far for a partial content upload.
+    ///
+    /// Returns `None` if no upload of the content is in progress.
+    pub async fn get_content_progress(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<u64>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state.content_progress.get(digest).copied())
+    }
+
+    /// Gets the package records of the given log, handling a `since` record
+    /// that is not a validated record of the log according to `since_behavior`.
+    pub async fn get_package_records_with_since_behavior(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+        since_behavior: SinceBehavior,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        if !state.checkpoints.contains_key(&registry_log_length) {
+            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
+        };
+
+        let start_log_idx = match since {
+            Some(since) => {
+                match state
+                    .records
+                    .get(log_id)
+                    .and_then(|records| records.get(since))
+                {
+                    Some(RecordStatus::Validated(record)) => record.index + 1,
+                    _ => match since_behavior {
+                        // If record not found or not in validated state, start from beginning
+                        SinceBehavior::Lenient => 0,
+                        SinceBehavior::Error => {
+                            return Err(DataStoreError::InvalidSinceRecord(since.clone()))
+                        }
+                    },
+                }
+            }
+            None => 0,
+        };
+
+        Ok(log
+            .entries
+            .iter()
+            .skip(start_log_idx)
+            .take_while(|entry| entry.registry_index < registry_log_length)
+            .map(|entry| PublishedProtoEnvelope {
+                envelope: entry.record_content.clone(),
+                registry_index: entry.registry_index,
+            })
+            .take(self.page_limit(limit))
+            .collect())
+    }
+
+    /// Gets the ids of the keys authorized to sign checkpoints by the given operator log.
+    pub async fn get_checkpoint_signing_keys(
+        &self,
+        operator_log_id: &LogId,
+    ) -> Result<Vec<KeyID>, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        Ok(log.state.checkpoint_signing_keys().cloned().collect())
+    }
+
+    /// Gets the namespaces defined by the given operator log that the key is
+    /// granted to publish packages to.
+    ///
+    /// The operator log does not grant keys access to individual namespaces,
+    /// so a key is granted the namespaces it defined for as long as it keeps
+    /// the permission to define namespaces. Imported namespaces are never
+    /// included, as packages cannot be published to them.
+    pub async fn get_publishable_namespaces_for_key(
+        &self,
+        operator_log_id: &LogId,
+        key_id: &KeyID,
+    ) -> Result<Vec<String>, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        if !log.state.key_has_permission_to_define_namespaces(key_id) {
+            return Ok(Vec::new());
+        }
+
+        Ok(log
+            .entries
+            .iter()
+            .filter(|entry| entry.record_content.key_id() == key_id)
+            .flat_map(|entry| entry.record_content.as_ref().entries.iter())
+            .filter_map(|entry| match entry {
+                OperatorEntry::DefineNamespace { namespace } => Some(namespace),
+                _ => None,
+            })
+            .filter(|namespace| {
+                matches!(
+                    log.state.namespace_state(namespace),
+                    Some(operator::NamespaceState::Defined)
+                )
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Determines why the given key may not sign checkpoints for the operator log.
+    ///
+    /// Returns `None` if the key is authorized to sign checkpoints.
+    pub async fn checkpoint_signing_denial_reason(
+        &self,
+        operator_log_id: &LogId,
+        key_id: &KeyID,
+    ) -> Result<Option<CheckpointSigningDenial>, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        Ok(super::checkpoint_signing_denial(&log.state, key_id))
+    }
+
+    /// Gets the init record (the first entry) of the given package log.
+    pub async fn get_init_record(
+        &self,
+        log_id: &LogId,
+    ) -> Result<super::Record<package::PackageRecord>, DataStoreError> {
+        let state = self.state.read().await;
+        let record_id = state.init_record_id(log_id)?;
+        state.package_record(log_id, record_id)
+    }
+
+    /// Gets the init record (the first entry) of the given operator log.
+    pub async fn get_operator_init_record(
+        &self,
+        log_id: &LogId,
+    ) -> Result<super::Record<operator::OperatorRecord>, DataStoreError> {
+        let state = self.state.read().await;
+        let record_id = state.init_record_id(log_id)?;
+        state.operator_record(log_id, record_id)
+    }
+
+    /// Gets the id of the log of the package with the given name.
+    ///
+    /// Aliases set with `set_package_alias` are consulted if no log has the
+    /// name; returns `None` if neither a log nor an alias has the name.
+    pub async fn get_log_id_for_package(
+        &self,
+        package_name: &PackageName,
+    ) -> Result<Option<LogId>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .package_names
+            .iter()
+            .find(|(_, name)| name.as_ref() == Some(package_name))
+            .map(|(log_id, _)| log_id)
+            .or_else(|| state.package_aliases.get(package_name))
+            .cloned())
+    }
+
+    /// Gets the pending package records stored at least `older_than` ago that
+    /// are still missing content, along with the missing content digests.
+    pub async fn get_stale_pending_content(
+        &self,
+        older_than: Duration,
+    ) -> Result<Vec<(LogId, RecordId, IndexSet<AnyHash>)>, DataStoreError> {
+        let cutoff = SystemTime::now()
+            .checked_sub(older_than)
+            .unwrap_or(UNIX_EPOCH);
+
+        let state = self.state.read().await;
+        Ok(state
+            .records
+            .iter()
+            .flat_map(|(log_id, records)| {
+                records
+                    .iter()
+                    .filter_map(move |(record_id, status)| match status {
+                        RecordStatus::Pending(PendingRecord::Package {
+                            missing,
+                            created_at,
+                            ..
+                        }) if !missing.is_empty() && *created_at <= cutoff => {
+                            Some((log_id.clone(), record_id.clone(), missing.clone()))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect())
+    }
+
+    /// Gets up to `limit` rejected records along with their rejection reasons.
+    pub async fn get_rejected_records(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<RejectedRecordInfo>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .records
+            .iter()
+            .flat_map(|(log_id, records)| {
+                records.iter().filter_map(move |(record_id, status)| {
+                    let (kind, reason) = match status {
+                        RecordStatus::Rejected(RejectedRecord::Operator { reason, .. }) => {
+                            (LogKind::Operator, reason)
+                        }
+                        RecordStatus::Rejected(RejectedRecord::Package { reason, .. }) => {
+                            (LogKind::Package, reason)
+                        }
+                        _ => return None,
+                    };
+
+                    Some(RejectedRecordInfo {
+                        log_id: log_id.clone(),
+                        record_id: record_id.clone(),
+                        kind,
+                        reason: reason.clone(),
+                    })
+                })
+            })
+            .take(limit)
+            .collect())
+    }
+
+    /// Revalidates a package log from scratch.
+    ///
+    /// Each committed record of the log is validated in order against a fresh
+    /// log state, and the resulting state is compared to the stored state.
+    pub async fn revalidate_log(&self, log_id: &LogId) -> Result<(), DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let mut validator = package::LogState::default();
+        for entry in &log.entries {
+            let leaf = state
+                .log_leafs
+                .get(&entry.registry_index)
+                .ok_or(DataStoreError::LogLeafNotFound(entry.registry_index))?;
+            validator = validator
+                .validate(&entry.record_content)
+                .map_err(|source| DataStoreError::RevalidationFailed {
+                    record_id: leaf.record_id.as_ref().clone(),
+                    source,
+                })?;
+        }
+
+        if validator != log.state {
+            return Err(DataStoreError::LogStateMismatch(log_id.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Gets package names from log IDs, omitting log IDs that are not known.
+    ///
+    /// Unlike `get_package_names`, an unknown log ID does not fail the lookup.
+    pub async fn get_package_names_lenient(
+        &self,
+        log_ids: &[LogId],
+    ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError> {
+        let state = self.state.read().await;
+
+        Ok(log_ids
+            .iter()
+            .filter_map(|log_id| {
+                state
+                    .package_names
+                    .get(log_id)
+                    .map(|name| (log_id.clone(), name.clone()))
+            })
+            .collect())
+    }
+
+    /// Gets the package records that reference the given content digest.
+    pub async fn get_records_with_content(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Vec<(LogId, RecordId)>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .content_to_records
+            .get(digest)
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    /// Gets up to `limit` committed records, of any kind, in registry order
+    /// starting at the given registry index.
+    pub async fn get_records_in_registry_order(
+        &self,
+        start: RegistryIndex,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, RecordKind)>, DataStoreError> {
+        let state = self.state.read().await;
+
+        (start..start.saturating_add(limit).min(state.log_leafs.len()))
+            .map(|registry_index| {
+                let leaf = state
+                    .log_leafs
+                    .get(&registry_index)
+                    .ok_or(DataStoreError::LogLeafNotFound(registry_index))?;
+                let log_id = leaf.log_id.as_ref();
+                let record_id = leaf.record_id.as_ref();
+
+                let index = match state
+                    .records
+                    .get(log_id)
+                    .and_then(|records| records.get(record_id))
+                {
+                    Some(RecordStatus::Validated(r)) => r.index,
+                    _ => return Err(DataStoreError::RecordNotFound(record_id.clone())),
+                };
+
+                let record = if let Some(log) = state.operators.get(log_id) {
+                    RecordKind::Operator(PublishedProtoEnvelope {
+                        envelope: log.entries[index].record_content.clone(),
+                        registry_index,
+                    })
+                } else if let Some(log) = state.packages.get(log_id) {
+                    RecordKind::Package(PublishedProtoEnvelope {
+                        envelope: log.entries[index].record_content.clone(),
+                        registry_index,
+                    })
+                } else {
+                    return Err(DataStoreError::LogNotFound(log_id.clone()));
+                };
+
+                Ok((registry_index, record))
+            })
+            .collect()
+    }
+
+    /// Gets up to `limit` leafs of records committed after the given time,
+    /// in registry order.
+    pub async fn get_records_committed_after(
+        &self,
+        time: SystemTime,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, LogLeaf)>, DataStoreError> {
+        let state = self.state.read().await;
+
+        let mut leafs = Vec::new();
+        for (&registry_index, leaf) in &state.log_leafs {
+            if leafs.len() >= limit {
+                break;
+            }
+
+            let log_id = leaf.log_id.as_ref();
+            let record_id = leaf.record_id.as_ref();
+            let index = match state
+                .records
+                .get(log_id)
+                .and_then(|records| records.get(record_id))
+            {
+                Some(RecordStatus::Validated(r)) => r.index,
+                _ => return Err(DataStoreError::RecordNotFound(record_id.clone())),
+            };
+
+            let committed_at = if let Some(log) = state.operators.get(log_id) {
+                log.entries[index].committed_at
+            } else if let Some(log) = state.packages.get(log_id) {
+                log.entries[index].committed_at
+            } else {
+                return Err(DataStoreError::LogNotFound(log_id.clone()));
+            };
+
+            if committed_at > time {
+                leafs.push((registry_index, leaf.to_log_leaf()));
+            }
+        }
+
+        Ok(leafs)
+    }
+
+    /// Gets the root of the registry log over all committed leafs.
+    ///
+    /// The root is maintained as records are committed rather than computed
+    /// from all leafs.
+    pub async fn get_current_root(&self) -> Result<AnyHash, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state.leaf_accumulator.0.checkpoint().root().into())
+    }
+
+    /// Gets the first checkpoint that includes the given registry index.
+    ///
+    /// Returns `None` if the index has not been checkpointed yet.
+    pub async fn get_covering_checkpoint(
+        &self,
+        registry_index: RegistryIndex,
+    ) -> Result<Option<SerdeEnvelope<TimestampedCheckpoint>>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .checkpoints
+            .iter()
+            .filter(|(&log_length, _)| log_length > registry_index)
+            .min_by_key(|(&log_length, _)| log_length)
+            .map(|(_, c)| c.envelope.clone()))
+    }
+
+    /// Gets all signatures of the checkpoint of the given log length.
+    ///
+    /// The signature the checkpoint was stored with comes first, followed by
+    /// any co-signatures.
+    pub async fn get_checkpoint_signatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<SerdeEnvelope<TimestampedCheckpoint>>, DataStoreError> {
+        let state = self.state.read().await;
+        let checkpoint = state
+            .checkpoints
+            .get(&log_length)
+            .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+
+        Ok(std::iter::once(&checkpoint.envelope)
+            .chain(&checkpoint.cosignatures)
+            .cloned()
+            .collect())
+    }
+
+    /// Gets the metadata stored with the checkpoint of the given log length.
+    pub async fn get_checkpoint_metadata(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<IndexMap<String, String>, DataStoreError> {
+        let state = self.state.read().await;
+        let checkpoint = state
+            .checkpoints
+            .get(&log_length)
+            .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+        Ok(checkpoint.metadata.clone())
+    }
+
+    /// Gets up to `limit` logs with committed records, most recently updated first.
+    pub async fn get_recently_updated_logs(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(LogId, SystemTime)>, DataStoreError> {
+        let state = self.state.read().await;
+
+        let mut logs = state
+            .operators
+            .iter()
+            .filter(|(_, log)| !log.entries.is_empty())
+            .map(|(log_id, log)| (log_id.clone(), log.updated_at))
+            .chain(
+                state
+                    .packages
+                    .iter()
+                    .filter(|(_, log)| !log.entries.is_empty())
+                    .map(|(log_id, log)| (log_id.clone(), log.updated_at)),
+            )
+            .collect::<Vec<_>>();
+        logs.sort_by(|(_, a), (_, b)| b.cmp(a));
+        logs.truncate(limit);
+        Ok(logs)
+    }
+
+    /// Exports a package log as a self-contained bundle.
+    pub async fn export_log(&self, log_id: &LogId) -> Result<LogBundle, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let records = log
+            .entries
+            .iter()
+            .map(|entry| {
+                let leaf = state
+                    .log_leafs
+                    .get(&entry.registry_index)
+                    .ok_or(DataStoreError::LogLeafNotFound(entry.registry_index))?;
+                Ok(BundledRecord {
+                    record_id: leaf.record_id.as_ref().clone(),
+                    record: PublishedProtoEnvelope {
+                        envelope: entry.record_content.clone(),
+                        registry_index: entry.registry_index,
+                    }
+                    .into(),
+                })
+            })
+            .collect::<Result<_, DataStoreError>>()?;
+
+        let checkpoint = log.entries.last().and_then(|head| {
+            state
+                .checkpoints
+                .iter()
+                .filter(|(len, _)| **len > head.registry_index)
+                .min_by_key(|(len, _)| **len)
+                .map(|(_, checkpoint)| checkpoint.envelope.clone())
+        });
+
+        Ok(LogBundle {
+            log_id: log_id.clone(),
+            package_name: state.package_names.get(log_id).cloned().flatten(),
+            records,
+            checkpoint,
+        })
+    }
+
+    /// Builds the inclusion proof for the leaf at the given registry index
+    /// in the log at the checkpoint of `checkpoint_length`.
+    pub async fn get_inclusion_proof<D: SupportedDigest>(
+        &self,
+        registry_index: RegistryIndex,
+        checkpoint_length: RegistryLen,
+    ) -> Result<LogProofBundle<D, LogLeaf>, DataStoreError> {
+        if registry_index >= checkpoint_length {
+            return Err(DataStoreError::InclusionIndexOutOfRange {
+                index: registry_index,
+                length: checkpoint_length,
+            });
+        }
+
+        let state = self.state.read().await;
+        if !state.checkpoints.contains_key(&checkpoint_length) {
+            return Err(DataStoreError::CheckpointNotFound(checkpoint_length));
+        }
+
+        let (log, nodes) = state.verifiable_log::<D>(checkpoint_length);
+        let leaf = nodes
+            .get(&registry_index)
+            .ok_or(DataStoreError::LogLeafNotFound(registry_index))?;
+        let proof = log.prove_inclusion(*leaf, checkpoint_length);
+        LogProofBundle::bundle(vec![], vec![proof], &log)
+            .map_err(|e| DataStoreError::ProofBundleFailed(e.to_string()))
+    }
+
+    /// Builds the consistency proof that the log at the checkpoint of
+    /// `to_length` is an append-only extension of the log at the checkpoint
+    /// of `from_length`.
+    pub async fn get_consistency_proof<D: SupportedDigest>(
+        &self,
+        from_length: RegistryLen,
+        to_length: RegistryLen,
+    ) -> Result<LogProofBundle<D, LogLeaf>, DataStoreError> {
+        if from_length > to_length {
+            return Err(DataStoreError::InvalidConsistencyRange {
+                from: from_length,
+                to: to_length,
+            });
+        }
+
+        let state = self.state.read().await;
+        for length in [from_length, to_length] {
+            if !state.checkpoints.contains_key(&length) {
+                return Err(DataStoreError::CheckpointNotFound(length));
+            }
+        }
+
+        let (log, _) = state.verifiable_log::<D>(to_length);
+        let proof = log.prove_consistency(from_length, to_length);
+        LogProofBundle::bundle(vec![proof], vec![], &log)
+            .map_err(|e| DataStoreError::ProofBundleFailed(e.to_string()))
+    }
+
+    /// Finds the registry indices in `[0, max_index]` that have no log leaf.
+    ///
+    /// An empty result means the registry log is contiguous.
+    pub async fn find_index_gaps(&self) -> Result<Vec<RegistryIndex>, DataStoreError> {
+        let state = self.state.read().await;
+        let max_index = match state.log_leafs.keys().max() {
+            Some(max_index) => *max_index,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok((0..=max_index)
+            .filter(|index| !state.log_leafs.contains_key(index))
+            .collect())
+    }
+
+    /// Gets the operator log that authorized publishing to the given package log.
+    ///
+    /// The association is recorded when a record of the package log is
+    /// stored while an operator log defines the package's namespace; returns
+    /// `None` if no such record has been stored.
+    pub async fn get_governing_operator(
+        &self,
+        package_log_id: &LogId,
+    ) -> Result<Option<LogId>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state.governing_operators.get(package_log_id).cloned())
+    }
+
+    /// Gets the hash of the current state of the given package log.
+    pub async fn get_package_log_state_hash(
+        &self,
+        log_id: &LogId,
+    ) -> Result<AnyHash, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        Ok(log_state_hash(&log.state))
+    }
+
+    /// Previews committing a record to the given package log.
+    ///
+    /// Returns the hash the log state would have after the record is validated,
+    /// without modifying the store.
+    pub async fn preview_commit_package_record(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<AnyHash, DataStoreError> {
+        let state = self.state.read().await;
+        let log_state = state
+            .packages
+            .get(log_id)
+            .map(|log| log.state.clone())
+            .unwrap_or_default()
+            .validate(record)?;
+
+        Ok(log_state_hash(&log_state))
+    }
+
+    /// Gets the log lengths of all stored checkpoints, in ascending order.
+    pub async fn get_checkpoint_lengths(&self) -> Result<Vec<RegistryLen>, DataStoreError> {
+        let state = self.state.read().await;
+        let mut lengths: Vec<_> = state.checkpoints.keys().copied().collect();
+        lengths.sort_unstable();
+        Ok(lengths)
+    }
+
+    /// Streams the records of a package log in descending registry index
+    /// order, starting from the last record before the given registry log length.
+    pub async fn stream_package_records_reverse(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<
+                        Item = Result<
+                            PublishedProtoEnvelope<package::PackageRecord>,
+                            DataStoreError,
+                        >,
+                    > + Send,
+            >,
+        >,
+        DataStoreError,
+    > {
+        let state = self.state.read().await;
+
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        if !state.checkpoints.contains_key(&registry_log_length) {
+            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
+        };
+
+        let mut records = log
+            .entries
+            .iter()
+            .take_while(|entry| entry.registry_index < registry_log_length)
+            .map(|entry| {
+                Ok(PublishedProtoEnvelope {
+                    envelope: entry.record_content.clone(),
+                    registry_index: entry.registry_index,
+                })
+            })
+            .collect::<Vec<_>>();
+        records.reverse();
+        Ok(Box::pin(futures::stream::iter(records)))
+    }
+
+    /// Gets the digests of all content declared by a package record.
+    ///
+    /// The digests are returned regardless of whether the content is missing.
+    pub async fn get_record_content_digests(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<IndexSet<AnyHash>, DataStoreError> {
+        use warg_protocol::Record;
+
+        let state = self.state.read().await;
+        let record = state.package_record(log_id, record_id)?;
+        Ok(record
+            .envelope
+            .as_ref()
+            .contents()
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+
+    /// Estimates the number of bytes held by the store.
+    ///
+    /// The estimate accounts for the encoded contents of records and the
+    /// fixed size of leafs, checkpoints, and package names; it is not exact.
+    pub async fn estimate_memory_bytes(&self) -> Result<u64, DataStoreError> {
+        use std::mem::size_of;
+
+        let state = self.state.read().await;
+        let entry_bytes = size_of::<LogId>() + size_of::<RecordId>() + size_of::<RecordStatus>();
+        let record_bytes = state
+            .records
+            .values()
+            .flat_map(|records| records.values())
+            .map(|status| {
+                entry_bytes
+                    + match status {
+                        RecordStatus::Pending(PendingRecord::Operator { record }) => {
+                            record.as_ref().map_or(0, |r| r.content_bytes().len())
+                        }
+                        RecordStatus::Pending(PendingRecord::Package {
+                            record,
+                            missing,
+                            unvalidated,
+                            ..
+                        }) => {
+                            record.as_ref().map_or(0, |r| r.content_bytes().len())
+                                + (missing.len() + unvalidated.len()) * size_of::<AnyHash>()
+                        }
+                        RecordStatus::Rejected(RejectedRecord::Operator { record, reason }) => {
+                            record.content_bytes().len() + reason.len()
+                        }
+                        RecordStatus::Rejected(RejectedRecord::Package { record, reason }) => {
+                            record.content_bytes().len() + reason.len()
+                        }
+                        // Validated records are counted with the entries of their log
+                        RecordStatus::Validated(_) => 0,
+                    }
+            })
+            .sum::<usize>();
+        let log_bytes = state
+            .operators
+            .values()
+            .flat_map(|log| &log.entries)
+            .map(|entry| entry.record_content.content_bytes().len())
+            .chain(
+                state
+                    .packages
+                    .values()
+                    .flat_map(|log| &log.entries)
+                    .map(|entry| entry.record_content.content_bytes().len()),
+            )
+            .sum::<usize>();
+        let leaf_bytes = state.log_leafs.len()
+            * (size_of::<Leaf>() + size_of::<LogId>() + size_of::<RecordId>());
+        let checkpoint_bytes = state
+            .checkpoints
+            .values()
+            .map(|checkpoint| {
+                (1 + checkpoint.cosignatures.len())
+                    * size_of::<SerdeEnvelope<TimestampedCheckpoint>>()
+                    + checkpoint
+                        .metadata
+                        .iter()
+                        .map(|(k, v)| k.len() + v.len())
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+        let name_bytes = state
+            .package_names
+            .values()
+            .map(|name| size_of::<LogId>() + name.as_ref().map_or(0, |n| n.as_ref().len()))
+            .sum::<usize>();
+
+        Ok((record_bytes + log_bytes + leaf_bytes + checkpoint_bytes + name_bytes) as u64)
+    }
+
+    /// Takes a snapshot of the current state of the store.
+    pub async fn snapshot(&self) -> StoreSnapshot {
+        StoreSnapshot {
+            state: self.state.read().await.clone(),
+        }
+    }
+
+    /// Gets the half-open range of registry indices committed after the
+    /// latest checkpoint.
+    ///
+    /// Returns `None` if every committed leaf is covered by a checkpoint.
+    pub async fn get_uncheckpointed_range(
+        &self,
+    ) -> Result<Option<(RegistryIndex, RegistryIndex)>, DataStoreError> {
+        let state = self.state.read().await;
+        let start = state
+            .checkpoints
+            .last()
+            .map(|(_, c)| c.envelope.as_ref().checkpoint.log_length)
+            .unwrap_or_default();
+        let end = state.log_leafs.len();
+
+        Ok((start < end).then_some((start, end)))
+    }
+
+    /// Gets the state of a content digest of a pending package record.
+    ///
+    /// Digests that were not missing when the record was stored are
+    /// considered validated.
+    pub async fn get_content_state(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<ContentState, DataStoreError> {
+        let state = self.state.read().await;
+        let status = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Package {
+                missing,
+                unvalidated,
+                ..
+            }) => Ok(if missing.contains(digest) {
+                ContentState::Missing
+            } else if unvalidated.contains(digest) {
+                ContentState::Present
+            } else {
+                ContentState::Validated
+            }),
+            _ => Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
+
+    /// Gets the status of a record of either an operator or a package log.
+    ///
+    /// Returns `None` if the record is not known to the store.
+    pub async fn record_status(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<super::RecordStatus>, DataStoreError> {
+        let state = self.state.read().await;
+        let Some(status) = state
+            .records
+            .get(log_id)
+            .and_then(|records| records.get(record_id))
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(match status {
+            RecordStatus::Pending(PendingRecord::Package { missing, .. })
+                if !missing.is_empty() =>
+            {
+                super::RecordStatus::MissingContent(missing.iter().cloned().collect())
+            }
+            RecordStatus::Pending(_) => super::RecordStatus::Pending,
+            RecordStatus::Rejected(
+                RejectedRecord::Operator { reason, .. } | RejectedRecord::Package { reason, .. },
+            ) => super::RecordStatus::Rejected(reason.clone()),
+            RecordStatus::Validated(r) => {
+                let published_length = state
+                    .checkpoints
+                    .last()
+                    .map(|(_, c)| c.envelope.as_ref().checkpoint.log_length)
+                    .unwrap_or_default();
+
+                if r.registry_index < published_length {
+                    super::RecordStatus::Published
+                } else {
+                    super::RecordStatus::Validated
+                }
+            }
+        }))
+    }
+
+    /// Gets the signed content bytes of a record of either an operator or a
+    /// package log, regardless of the record's status.
+    pub async fn get_record_content_bytes(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Vec<u8>, DataStoreError> {
+        let state = self.state.read().await;
+        let status = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        let bytes = match status {
+            RecordStatus::Pending(PendingRecord::Operator {
+                record: Some(record),
+            }) => record.content_bytes(),
+            RecordStatus::Pending(PendingRecord::Package {
+                record: Some(record),
+                ..
+            }) => record.content_bytes(),
+            RecordStatus::Pending(_) => {
+                return Err(DataStoreError::RecordNotFound(record_id.clone()))
+            }
+            RecordStatus::Rejected(RejectedRecord::Operator { record, .. }) => {
+                record.content_bytes()
+            }
+            RecordStatus::Rejected(RejectedRecord::Package { record, .. }) => {
+                record.content_bytes()
+            }
+            RecordStatus::Validated(r) => {
+                if let Some(log) = state.operators.get(log_id) {
+                    log.entries[r.index].record_content.content_bytes()
+                } else if let Some(log) = state.packages.get(log_id) {
+                    log.entries[r.index].record_content.content_bytes()
+                } else {
+                    return Err(DataStoreError::LogNotFound(log_id.clone()));
+                }
+            }
+        };
+
+        Ok(bytes.to_vec())
+    }
+
+    /// Gets the ids of the logs with pending package records that are still
+    /// missing content, in the order the logs were first stored.
+    pub async fn get_logs_awaiting_content(&self) -> Result<Vec<LogId>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .records
+            .iter()
+            .filter(|(_, records)| {
+                records.values().any(|status| {
+                    matches!(
+                        status,
+                        RecordStatus::Pending(PendingRecord::Package { missing, .. })
+                            if !missing.is_empty()
+                    )
+                })
+            })
+            .map(|(log_id, _)| log_id.clone())
+            .collect())
+    }
+
+    /// Streams all committed leafs in registry order, `chunk_size` leafs at a
+    /// time.
+    ///
+    /// The read lock is only held while each chunk is copied, so leafs
+    /// committed while the stream is consumed are also yielded.
+    pub fn stream_validated_records_chunked(
+        &self,
+        chunk_size: usize,
+    ) -> impl Stream<Item = LogLeaf> + Send {
+        let chunk_size = chunk_size.max(1);
+        futures::stream::unfold(
+            (self.state.clone(), 0),
+            move |(state, cursor): (Arc<RwLock<State>>, RegistryIndex)| async move {
+                let chunk = {
+                    let guard = state.read().await;
+                    (cursor..cursor + chunk_size)
+                        .map_while(|index| guard.log_leafs.get(&index))
+                        .map(Leaf::to_log_leaf)
+                        .collect::<Vec<_>>()
+                };
+
+                if chunk.is_empty() {
+                    return None;
+                }
+
+                let cursor = cursor + chunk.len();
+                Some((futures::stream::iter(chunk), (state, cursor)))
+            },
+        )
+        .flatten()
+    }
+
+    /// Finds the ids of the records of a log, in any state, whose string form
+    /// starts with the given prefix.
+    ///
+    /// Returns `TooManyMatches` if more than 50 records match.
+    pub async fn find_records_by_id_prefix(
+        &self,
+        log_id: &LogId,
+        prefix: &str,
+    ) -> Result<Vec<RecordId>, DataStoreError> {
+        let state = self.state.read().await;
+        let records = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let matches = records
+            .keys()
+            .filter(|record_id| record_id.to_string().starts_with(prefix))
+            .take(MAX_PREFIX_MATCHES + 1)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if matches.len() > MAX_PREFIX_MATCHES {
+            return Err(DataStoreError::TooManyMatches {
+                prefix: prefix.to_string(),
+                limit: MAX_PREFIX_MATCHES,
+            });
+        }
+
+        Ok(matches)
+    }
+
+    /// Gets the ids of the keys that signed the committed records of an
+    /// operator or package log, in the order they were first seen.
+    pub async fn get_signing_keys(
+        &self,
+        log_id: &LogId,
+    ) -> Result<IndexSet<KeyID>, DataStoreError> {
+        let state = self.state.read().await;
+        if let Some(log) = state.operators.get(log_id) {
+            Ok(log
+                .entries
+                .iter()
+                .map(|entry| entry.record_content.key_id().clone())
+                .collect())
+        } else if let Some(log) = state.packages.get(log_id) {
+            Ok(log
+                .entries
+                .iter()
+                .map(|entry| entry.record_content.key_id().clone())
+                .collect())
+        } else {
+            Err(DataStoreError::LogNotFound(log_id.clone()))
+        }
+    }
+
+    /// Checks that a log id is the id derived from the given package name.
+    ///
+    /// The name is hashed with the algorithm of the given log id.
+    pub fn verify_log_id(
+        &self,
+        log_id: &LogId,
+        package_name: &PackageName,
+    ) -> Result<bool, DataStoreError> {
+        let expected = match AnyHash::from(log_id.clone()).algorithm() {
+            HashAlgorithm::Sha256 => LogId::package_log::<Sha256>(package_name),
+            _ => return Ok(false),
+        };
+        Ok(*log_id == expected)
+    }
+
+    /// Determines if a record is a package record, which can have content,
+    /// rather than an operator record, which never does.
+    pub async fn record_supports_content(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<bool, DataStoreError> {
+        let state = self.state.read().await;
+        let status = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        Ok(match status {
+            RecordStatus::Pending(PendingRecord::Operator { .. })
+            | RecordStatus::Rejected(RejectedRecord::Operator { .. }) => false,
+            RecordStatus::Pending(PendingRecord::Package { .. })
+            | RecordStatus::Rejected(RejectedRecord::Package { .. }) => true,
+            RecordStatus::Validated(_) => state.packages.contains_key(log_id),
+        })
+    }
+
+    /// Gets the ids of all records that are currently pending.
+    pub async fn get_pending_records(&self) -> Result<Vec<(LogId, RecordId)>, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state
+            .records
+            .iter()
+            .flat_map(|(log_id, records)| {
+                records
+                    .iter()
+                    .filter_map(move |(record_id, status)| match status {
+                        RecordStatus::Pending(_) => Some((log_id.clone(), record_id.clone())),
+                        _ => None,
+                    })
+            })
+            .collect())
+    }
+}
+
+impl Default for MemoryDataStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[axum::async_trait]
+impl ReadOnlyDataStore for ReadHandle {
+    async fn get_all_checkpoints(
+        &self,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+        DataStoreError,
+    > {
+        Ok(Box::pin(futures::stream::empty()))
+    }
+
+    async fn get_all_validated_records(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        self.get_all_validated_records_from(0).await
+    }
+
+    async fn get_all_validated_records_from(
+        &self,
+        start: RegistryIndex,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        let state = self.state.read().await;
+        let leafs = state
+            .log_leafs
+            .iter()
+            .filter(|(index, _)| **index >= start)
+            .map(|(_, leaf)| Ok(leaf.to_log_leaf()))
+            .collect::<Vec<_>>();
+        Ok(Box::pin(futures::stream::iter(leafs)))
+    }
+
+    async fn get_log_leafs_starting_with_registry_index(
+        &self,
+        starting_index: RegistryIndex,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, LogLeaf)>, DataStoreError> {
+        let state = self.state.read().await;
+
+        // A starting index past the end of the log yields no leafs
+        let limit = limit.min(state.log_leafs.len().saturating_sub(starting_index));
+
+        let mut leafs = Vec::with_capacity(limit);
+        for entry in starting_index..starting_index + limit {
+            match state.log_leafs.get(&entry) {
+                Some(leaf) => leafs.push((entry, leaf.to_log_leaf())),
+                None => break,
+            }
+        }
+
+        Ok(leafs)
+    }
+
+    async fn get_log_leafs_with_registry_index(
+        &self,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let state = self.state.read().await;
+
+        let mut leafs = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match state.log_leafs.get(entry) {
+                Some(leaf) => leafs.push(leaf.to_log_leaf()),
+                None => return Err(DataStoreError::LogLeafNotFound(*entry)),
+            }
+        }
+
+        Ok(leafs)
+    }
+
+    async fn get_package_names(
+        &self,
+        log_ids: &[LogId],
+    ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError> {
+        let state = self.state.read().await;
+
+        log_ids
+            .iter()
+            .map(|log_id| {
+                if let Some(opt_package_name) = state.package_names.get(log_id) {
+                    Ok((log_id.clone(), opt_package_name.clone()))
+                } else {
+                    Err(DataStoreError::LogNotFound(log_id.clone()))
+                }
+            })
+            .collect::<Result<IndexMap<LogId, Option<PackageName>>, _>>()
+    }
+
+    async fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        let state = self.state.read().await;
+        let log = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let status = log
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
+                // Operator records have no content
+                Ok(false)
+            }
+            RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
+                Ok(missing.contains(digest))
+            }
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
+
+    async fn get_content_size(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<u64, DataStoreError> {
+        let state = self.state.read().await;
+        let status = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        let record = match status {
+            RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
+                record.as_ref().unwrap()
+            }
+            RecordStatus::Rejected(RejectedRecord::Package { record, .. }) => record,
+            RecordStatus::Validated(r) => match state.packages.get(log_id) {
+                Some(log) => &log.entries[r.index].record_content,
+                // Operator records have no content
+                None => return Ok(0),
+            },
+            // Operator records have no content
+            _ => return Ok(0),
+        };
+
+        use warg_protocol::Record;
+        Ok(record
+            .as_ref()
+            .contents()
+            .into_iter()
+            .filter_map(|digest| {
+                state
+                    .content_sizes
+                    .get(&(log_id.clone(), record_id.clone(), digest.clone()))
+            })
+            .sum())
+    }
+
+    async fn current_registry_length(&self) -> Result<RegistryLen, DataStoreError> {
+        let state = self.state.read().await;
+        Ok(state.log_leafs.len() as RegistryLen)
+    }
+
+    async fn get_store_stats(&self) -> Result<StoreStats, DataStoreError> {
+        let state = self.state.read().await;
+
+        // Package logs are named when their first record is stored
+        let mut stats = StoreStats {
+            package_logs: state.package_names.len() as u64,
+            checkpoints: state.checkpoints.len() as u64,
+            ..Default::default()
+        };
+
+        for (log_id, records) in &state.records {
+            if !state.package_names.contains_key(log_id) {
+                stats.operator_logs += 1;
+            }
+
+            for status in records.values() {
+                match status {
+                    RecordStatus::Pending(_) => stats.pending_records += 1,
+                    RecordStatus::Rejected(_) => stats.rejected_records += 1,
+                    RecordStatus::Validated(_) => stats.validated_records += 1,
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    async fn get_latest_checkpoint(
+        &self,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        let state = self.state.read().await;
+        let checkpoint = state
+            .checkpoints
+            .values()
+            .last()
+            .ok_or(DataStoreError::NoCheckpoint)?;
+        Ok(checkpoint.envelope.clone())
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        let state = self.state.read().await;
+        let checkpoint = state
+            .checkpoints
+            .get(&log_length)
+            .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+        Ok(checkpoint.envelope.clone())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id))]
+    async fn get_operator_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+
+        let log = state
+            .operators
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        if !state.checkpoints.contains_key(&registry_log_length) {
+            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
+        };
+
+        let start_log_idx = match since {
+            Some(since) => {
+                match state
+                    .records
+                    .get(log_id)
+                    .and_then(|records| records.get(since))
+                {
+                    Some(RecordStatus::Validated(record)) => record.index + 1,
+                    // If record not found or not in validated state, start from beginning
+                    _ => 0,
+                }
+            }
+            None => 0,
+        };
+
+        Ok(log
+            .entries
+            .iter()
+            .skip(start_log_idx)
+            .take_while(|entry| entry.registry_index < registry_log_length)
+            .map(|entry| PublishedProtoEnvelope {
+                envelope: entry.record_content.clone(),
+                registry_index: entry.registry_index,
+            })
+            .take(self.page_limit(limit))
+            .collect())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id))]
+    async fn get_package_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        self.get_package_records_with_since_behavior(
+            log_id,
+            registry_log_length,
+            since,
+            limit,
+            SinceBehavior::Lenient,
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id))]
+    async fn get_package_records_since_index(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since_index: Option<RegistryIndex>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        let state = self.state.read().await;
+
+        let log = state
+            .packages
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        if !state.checkpoints.contains_key(&registry_log_length) {
+            return Err(DataStoreError::CheckpointNotFound(registry_log_length));
+        };
+
+        // Entries are sorted by registry index, so find the first entry after `since_index`
+        let start_log_idx = match since_index {
+            Some(since_index) => log
+                .entries
+                .partition_point(|entry| entry.registry_index <= since_index),
+            None => 0,
+        };
+
+        Ok(log
+            .entries
+            .iter()
+            .skip(start_log_idx)
+            .take_while(|entry| entry.registry_index < registry_log_length)
+            .map(|entry| PublishedProtoEnvelope {
+                envelope: entry.record_content.clone(),
+                registry_index: entry.registry_index,
+            })
+            .take(self.page_limit(limit))
+            .collect())
+    }
+
+    async fn get_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<super::Record<operator::OperatorRecord>, DataStoreError> {
+        let state = self.state.read().await;
+        state.operator_record(log_id, record_id)
+    }
+
+    async fn get_head_record_id(&self, log_id: &LogId) -> Result<Option<RecordId>, DataStoreError> {
+        let state = self.state.read().await;
+        if !state.records.contains_key(log_id) {
+            return Err(DataStoreError::LogNotFound(log_id.clone()));
+        }
+
+        let last = match (state.operators.get(log_id), state.packages.get(log_id)) {
+            (Some(log), _) => log.entries.last().map(|e| e.registry_index),
+            (_, Some(log)) => log.entries.last().map(|e| e.registry_index),
+            (None, None) => None,
+        };
+
+        last.map(|registry_index| {
+            state
+                .log_leafs
+                .get(&registry_index)
+                .map(|leaf| leaf.record_id.as_ref().clone())
+                .ok_or(DataStoreError::LogLeafNotFound(registry_index))
+        })
+        .transpose()
+    }
+
+    async fn get_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<super::Record<package::PackageRecord>, DataStoreError> {
+        let key = (log_id.clone(), record_id.clone());
+        if let Some(record) = self
+            .record_cache
+            .as_ref()
+            .and_then(|cache| cache.lock().unwrap().get(&key))
+        {
+            return Ok(record);
+        }
+
+        let state = self.state.read().await;
+        let record = state.package_record(log_id, record_id)?;
+        if let (Some(cache), super::RecordStatus::Published) = (&self.record_cache, &record.status)
+        {
+            cache.lock().unwrap().insert(key, record.clone());
+        }
+
+        Ok(record)
+    }
+
+    async fn verify_package_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), DataStoreError> {
+        let state = self.state.read().await;
+        let key = match state
+            .packages
+            .get(log_id)
+            .and_then(|log| log.state.public_key(record.key_id()))
+        {
+            Some(key) => Some(key),
+            None => match record.as_ref().entries.first() {
+                Some(PackageEntry::Init { key, .. }) => Some(key),
+                _ => return Err(DataStoreError::UnknownKey(record.key_id().clone())),
+            },
+        }
+        .ok_or_else(|| DataStoreError::UnknownKey(record.key_id().clone()))?;
+
+        package::PackageRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+    }
+
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let state = self.state.read().await;
+        let key = match state
+            .operators
+            .get(log_id)
+            .and_then(|log| log.state.public_key(record.key_id()))
+        {
+            Some(key) => key,
+            None => match record.as_ref().entries.first() {
+                Some(OperatorEntry::Init { key, .. }) => key,
+                _ => return Err(DataStoreError::UnknownKey(record.key_id().clone())),
+            },
+        };
+
+        operator::OperatorRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+    }
+
+    async fn verify_can_publish_package(
+        &self,
+        operator_log_id: &LogId,
+        package_name: &PackageName,
+    ) -> Result<(), DataStoreError> {
+        let state = self.state.read().await;
+
+        let operator = &state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?
+            .state;
+
+        // verify namespace is defined and not imported
+        match operator.namespace_state(package_name.namespace()) {
+            Some(state) => match state {
+                operator::NamespaceState::Defined => {}
+                operator::NamespaceState::Imported { .. } => {
+                    return Err(DataStoreError::PackageNamespaceImported(
+                        package_name.namespace().to_string(),
+                    ))
+                }
+            },
+            None => {
+                return Err(super::namespace_not_defined(
+                    operator,
+                    package_name.namespace(),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn verify_timestamped_checkpoint_signature(
+        &self,
+        operator_log_id: &LogId,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let state = self.state.read().await;
+
+        let state = &state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?
+            .state;
+
+        super::verify_checkpoint_signature(state, ts_checkpoint)
+    }
+
+    async fn verify_timestamped_checkpoints(
+        &self,
+        operator_log_id: &LogId,
+        checkpoints: &[SerdeEnvelope<TimestampedCheckpoint>],
+    ) -> Result<(), DataStoreError> {
+        let state = self.state.read().await;
+
+        let state = &state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?
+            .state;
+
+        super::verify_checkpoint_signatures(state, checkpoints)
+    }
+
+    #[cfg(feature = "debug")]
+    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
+        let state = self.state.read().await;
+        Ok(state
+            .package_names
+            .values()
+            .filter_map(|opt_package_name| opt_package_name.clone())
+            .collect())
+    }
+}
+
+#[axum::async_trait]
+impl ReadOnlyDataStore for MemoryDataStore {
+    async fn get_all_checkpoints(
+        &self,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+        DataStoreError,
+    > {
+        self.reader.get_all_checkpoints().await
+    }
+
+    async fn get_all_validated_records(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        self.reader.get_all_validated_records().await
+    }
+
+    async fn get_all_validated_records_from(
+        &self,
+        start: RegistryIndex,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        self.reader.get_all_validated_records_from(start).await
+    }
+
+    async fn get_log_leafs_starting_with_registry_index(
+        &self,
+        starting_index: RegistryIndex,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, LogLeaf)>, DataStoreError> {
+        self.reader
+            .get_log_leafs_starting_with_registry_index(starting_index, limit)
+            .await
+    }
+
+    async fn get_log_leafs_with_registry_index(
+        &self,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.reader.get_log_leafs_with_registry_index(entries).await
+    }
+
+    async fn get_package_names(
+        &self,
+        log_ids: &[LogId],
+    ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError> {
+        self.reader.get_package_names(log_ids).await
+    }
+
+    async fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        self.reader
+            .is_content_missing(log_id, record_id, digest)
+            .await
+    }
+
+    async fn get_content_size(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<u64, DataStoreError> {
+        self.reader.get_content_size(log_id, record_id).await
+    }
+
+    async fn current_registry_length(&self) -> Result<RegistryLen, DataStoreError> {
+        self.reader.current_registry_length().await
+    }
+
+    async fn get_store_stats(&self) -> Result<StoreStats, DataStoreError> {
+        self.reader.get_store_stats().await
+    }
+
+    async fn get_latest_checkpoint(
+        &self,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.reader.get_latest_checkpoint().await
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.reader.get_checkpoint(log_length).await
+    }
+
+    async fn get_operator_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        self.reader
+            .get_operator_records(log_id, registry_log_length, since, limit)
+            .await
+    }
+
+    async fn get_package_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        self.reader
+            .get_package_records(log_id, registry_log_length, since, limit)
+            .await
+    }
+
+    async fn get_package_records_since_index(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since_index: Option<RegistryIndex>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        self.reader
+            .get_package_records_since_index(log_id, registry_log_length, since_index, limit)
+            .await
+    }
+
+    async fn get_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<super::Record<operator::OperatorRecord>, DataStoreError> {
+        self.reader.get_operator_record(log_id, record_id).await
+    }
+
+    async fn get_head_record_id(&self, log_id: &LogId) -> Result<Option<RecordId>, DataStoreError> {
+        self.reader.get_head_record_id(log_id).await
+    }
+
+    async fn get_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<super::Record<package::PackageRecord>, DataStoreError> {
+        self.reader.get_package_record(log_id, record_id).await
+    }
+
+    async fn verify_package_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.reader
+            .verify_package_record_signature(log_id, record)
+            .await
+    }
+
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.reader
+            .verify_operator_record_signature(log_id, record)
+            .await
+    }
+
+    async fn verify_can_publish_package(
+        &self,
+        operator_log_id: &LogId,
+        package_name: &PackageName,
+    ) -> Result<(), DataStoreError> {
+        self.reader
+            .verify_can_publish_package(operator_log_id, package_name)
+            .await
+    }
+
+    async fn verify_timestamped_checkpoint_signature(
+        &self,
+        operator_log_id: &LogId,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        self.reader
+            .verify_timestamped_checkpoint_signature(operator_log_id, ts_checkpoint)
+            .await
+    }
+
+    async fn verify_timestamped_checkpoints(
+        &self,
+        operator_log_id: &LogId,
+        checkpoints: &[SerdeEnvelope<TimestampedCheckpoint>],
+    ) -> Result<(), DataStoreError> {
+        self.reader
+            .verify_timestamped_checkpoints(operator_log_id, checkpoints)
+            .await
+    }
+
+    #[cfg(feature = "debug")]
+    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
+        self.reader.debug_list_package_names().await
+    }
+}
+
+#[axum::async_trait]
+impl DataStore for MemoryDataStore {
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn store_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+        self.ensure_record_size(record)?;
+
+        let mut state = self.state.write().await;
+        self.store_operator_locked(&mut state, log_id, record_id, record)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn reject_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        self.record_op(|| StoreOp::RejectOperatorRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            reason: reason.to_string(),
+        });
+
+        let status = state
+            .records
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get_mut(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        let record = match status {
+            RecordStatus::Pending(PendingRecord::Operator { record }) => record.take().unwrap(),
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        };
+
+        *status = RecordStatus::Rejected(RejectedRecord::Operator {
+            record,
+            reason: reason.to_string(),
+        });
+
+        self.record_transition(log_id, record_id, format!("rejected: {reason}"));
+        self.metrics.on_reject(LogKind::Operator);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id, %registry_index))]
+    async fn commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        let committed =
+            self.commit_operator_locked(&mut state, log_id, record_id, registry_index)?;
+
+        drop(state);
+        if committed {
+            self.notify_commit(registry_index);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id, %registry_index))]
+    async fn store_and_commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+        self.ensure_record_size(record)?;
+
+        // Check the index before storing so a failed commit leaves no pending record
+        let mut state = self.state.write().await;
+        if registry_index != state.log_leafs.len() {
+            return Err(DataStoreError::NonContiguousRegistryIndex {
+                expected: state.log_leafs.len(),
+                got: registry_index,
+            });
+        }
+
+        self.store_operator_locked(&mut state, log_id, record_id, record)?;
+        let committed =
+            self.commit_operator_locked(&mut state, log_id, record_id, registry_index)?;
+
+        drop(state);
+        if committed {
+            self.notify_commit(registry_index);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn store_package_record(
+        &self,
+        log_id: &LogId,
+        package_name: &PackageName,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        missing: &IndexSet<&AnyHash>,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+        self.ensure_record_size(record)?;
+
+        super::ensure_missing_in_contents(record, missing)?;
+
+        if self.verify_on_store {
+            self.verify_package_record_signature(log_id, record).await?;
+        }
+
+        let mut state = self.state.write().await;
+        ensure_record_not_stored(&state, log_id, record_id)?;
+
+        if let Some(Some(existing)) = state.package_names.get(log_id) {
+            if existing != package_name {
+                return Err(DataStoreError::PackageNameMismatch {
+                    existing: existing.to_string(),
+                    got: package_name.to_string(),
+                });
+            }
+        }
+
+        if let Some(owner_key) = state.package_reservations.get(package_name) {
+            let init_key = record
+                .as_ref()
+                .entries
+                .iter()
+                .find_map(|entry| match entry {
+                    PackageEntry::Init { key, .. } => Some(key.fingerprint()),
+                    _ => None,
+                });
+            if init_key.is_some_and(|key| key != *owner_key) {
+                return Err(DataStoreError::PackageNameReserved(package_name.clone()));
+            }
+        }
+
+        self.record_op(|| StoreOp::StorePackageRecord {
+            log_id: log_id.clone(),
+            package_name: package_name.clone(),
+            record_id: record_id.clone(),
+            record: record.clone(),
+            missing: missing.iter().map(|&d| d.clone()).collect(),
+        });
+        let prev = state.records.entry(log_id.clone()).or_default().insert(
+            record_id.clone(),
+            RecordStatus::Pending(PendingRecord::Package {
+                record: Some(record.clone()),
+                missing: missing.iter().map(|&d| d.clone()).collect(),
+                unvalidated: IndexSet::new(),
+                created_at: SystemTime::now(),
+            }),
+        );
+        state
+            .package_names
+            .insert(log_id.clone(), Some(package_name.clone()));
+        if let Some(operator_log_id) = state.defining_operator(package_name.namespace()).cloned() {
+            state
+                .governing_operators
+                .insert(log_id.clone(), operator_log_id);
+        }
+
+        {
+            use warg_protocol::Record;
+            for digest in record.as_ref().contents() {
+                state
+                    .content_to_records
+                    .entry(digest.clone())
+                    .or_default()
+                    .insert((log_id.clone(), record_id.clone()));
+            }
+        }
+
+        assert!(prev.is_none());
+        self.record_transition(log_id, record_id, "pending".to_string());
+        self.metrics.on_store(LogKind::Package);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn reject_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        self.record_op(|| StoreOp::RejectPackageRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            reason: reason.to_string(),
+        });
+
+        let status = state
+            .records
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get_mut(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        let record = match status {
+            RecordStatus::Pending(PendingRecord::Package { record, .. }) => record.take().unwrap(),
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        };
+
+        *status = RecordStatus::Rejected(RejectedRecord::Package {
+            record,
+            reason: reason.to_string(),
+        });
+
+        self.record_transition(log_id, record_id, format!("rejected: {reason}"));
+        self.metrics.on_reject(LogKind::Package);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id, %registry_index))]
+    async fn commit_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        let committed =
+            self.commit_package_locked(&mut state, log_id, record_id, registry_index)?;
+
+        let package_name = state.package_names.get(log_id).cloned().flatten();
+        drop(state);
+        if committed {
+            if let Some(package_name) = package_name {
+                self.record_publish_for_rate(package_name.namespace());
+            }
+            self.notify_commit(registry_index);
+        }
+
+        Ok(())
+    }
+
+    async fn set_content_present(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        content_size: Option<u64>,
+    ) -> Result<bool, DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        self.record_op(|| StoreOp::SetContentPresent {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            digest: digest.clone(),
+            content_size,
+        });
+
+        let State {
+            records,
+            content_sizes,
+            content_progress,
+            ..
+        } = &mut *state;
+
+        let log = records
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let status = log
+            .get_mut(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Operator { .. }) => {
+                // Operator records have no content, so conceptually already present
+                Ok(false)
+            }
+            RecordStatus::Pending(PendingRecord::Package {
+                missing,
+                unvalidated,
+                ..
+            }) => {
+                if let Some(size) = content_size {
+                    // The same content may be referenced by other records,
+                    // which must all agree on its size
+                    if let Some(&expected) = content_sizes
+                        .iter()
+                        .find(|((_, _, d), &expected)| d == digest && expected != size)
+                        .map(|(_, size)| size)
+                    {
+                        return Err(DataStoreError::ContentSizeMismatch {
+                            digest: digest.clone(),
+                            expected,
+                            got: size,
+                        });
+                    }
+
+                    content_sizes.insert((log_id.clone(), record_id.clone(), digest.clone()), size);
+                }
+
+                content_progress.swap_remove(digest);
+
+                if missing.is_empty() {
+                    return Ok(false);
+                }
+
+                // Return true if this was the last missing content
+                if missing.swap_remove(digest) {
+                    unvalidated.insert(digest.clone());
+
+                    // Sending only fails when there are no subscribers
+                    let _ = self.content_present.send((
+                        log_id.clone(),
+                        record_id.clone(),
+                        digest.clone(),
+                    ));
+                    return Ok(missing.is_empty());
+                }
+                Ok(false)
+            }
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
+
+    async fn set_content_validated(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        self.ensure_open()?;
+
+        let mut state = self.state.write().await;
+        self.record_op(|| StoreOp::SetContentValidated {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            digest: digest.clone(),
+        });
+
+        let status = state
+            .records
+            .get_mut(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get_mut(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        match status {
+            RecordStatus::Pending(PendingRecord::Package {
+                missing,
+                unvalidated,
+                ..
+            }) => {
+                if missing.contains(digest) {
+                    return Err(DataStoreError::ContentNotPresent(digest.clone()));
+                }
+
+                Ok(unvalidated.swap_remove(digest) && unvalidated.is_empty() && missing.is_empty())
+            }
+            _ => Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(%checkpoint_id))]
+    async fn store_checkpoint(
+        &self,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        self.store_checkpoint_with_metadata(checkpoint_id, ts_checkpoint, IndexMap::new())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::atomic::AtomicUsize, time::Duration};
+    use warg_crypto::{
+        hash::{Hash, HashAlgorithm, Sha256},
+        signing::{generate_p256_pair, PrivateKey},
+    };
+    use warg_protocol::registry::Checkpoint;
+
+    fn content_digest(content: &str) -> AnyHash {
+        Hash::<Sha256>::of(content).into()
+    }
+
+    fn package_record(
+        key: &PrivateKey,
+        prev: Option<RecordId>,
+        entries: Vec<PackageEntry>,
+    ) -> (RecordId, ProtoEnvelope<package::PackageRecord>) {
+        let record = ProtoEnvelope::signed_contents(
+            key,
+            package::PackageRecord {
+                prev,
+                version: package::PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries,
+            },
+        )
+        .unwrap();
+        (RecordId::package_record::<Sha256>(&record), record)
+    }
+
+    fn package_init_record(
+        key: &PrivateKey,
+        contents: &[&AnyHash],
+    ) -> (RecordId, ProtoEnvelope<package::PackageRecord>) {
+        let mut entries = vec![PackageEntry::Init {
+            hash_algorithm: HashAlgorithm::Sha256,
+            key: key.public_key(),
+        }];
+        entries.extend(
+            contents
+                .iter()
+                .enumerate()
+                .map(|(i, &content)| PackageEntry::Release {
+                    version: format!("1.0.{i}").parse().unwrap(),
+                    content: content.clone(),
+                }),
+        );
+        package_record(key, None, entries)
+    }
+
+    fn operator_record(
+        key: &PrivateKey,
+        prev: Option<RecordId>,
+        entries: Vec<OperatorEntry>,
+    ) -> (RecordId, ProtoEnvelope<operator::OperatorRecord>) {
+        let record = ProtoEnvelope::signed_contents(
+            key,
+            operator::OperatorRecord {
+                prev,
+                version: operator::OPERATOR_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries,
+            },
+        )
+        .unwrap();
+        (RecordId::operator_record::<Sha256>(&record), record)
+    }
+
+    fn operator_init_record(
+        key: &PrivateKey,
+        namespaces: &[&str],
+    ) -> (RecordId, ProtoEnvelope<operator::OperatorRecord>) {
+        let mut entries = vec![OperatorEntry::Init {
+            hash_algorithm: HashAlgorithm::Sha256,
+            key: key.public_key(),
+        }];
+        entries.extend(
+            namespaces
+                .iter()
+                .map(|namespace| OperatorEntry::DefineNamespace {
+                    namespace: namespace.to_string(),
+                }),
+        );
+        operator_record(key, None, entries)
+    }
+
+    fn test_package() -> (LogId, PackageName) {
+        named_package("test:package")
+    }
+
+    fn named_package(name: &str) -> (LogId, PackageName) {
+        let name = PackageName::new(name).unwrap();
+        (LogId::package_log::<Sha256>(&name), name)
+    }
+
+    fn checkpoint(
+        key: &PrivateKey,
+        log_length: RegistryLen,
+    ) -> SerdeEnvelope<TimestampedCheckpoint> {
+        checkpoint_at(key, log_length, SystemTime::now())
+    }
+
+    fn checkpoint_at(
+        key: &PrivateKey,
+        log_length: RegistryLen,
+        time: SystemTime,
+    ) -> SerdeEnvelope<TimestampedCheckpoint> {
+        SerdeEnvelope::signed_contents(
+            key,
+            TimestampedCheckpoint::new(
+                Checkpoint {
+                    log_root: content_digest(&format!("log-{log_length}")),
+                    log_length,
+                    map_root: content_digest(&format!("map-{log_length}")),
+                },
+                time,
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    async fn store_and_commit_operator(
+        store: &MemoryDataStore,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+        registry_index: RegistryIndex,
+    ) {
+        let log_id = LogId::operator_log::<Sha256>();
+        store
+            .store_operator_record(&log_id, record_id, record)
+            .await
+            .unwrap();
+        store
+            .commit_operator_record(&log_id, record_id, registry_index)
+            .await
+            .unwrap();
+    }
+
+    async fn store_and_commit_package(
+        store: &MemoryDataStore,
+        (log_id, name): &(LogId, PackageName),
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        registry_index: RegistryIndex,
+    ) {
+        store
+            .store_package_record(log_id, name, record_id, record, &IndexSet::new())
+            .await
+            .unwrap();
+        store
+            .commit_package_record(log_id, record_id, registry_index)
+            .await
+            .unwrap();
+    }
+
+    async fn store_checkpoint(store: &MemoryDataStore, key: &PrivateKey, log_length: RegistryLen) {
+        store
+            .store_checkpoint(
+                &content_digest(&format!("checkpoint-{log_length}")),
+                checkpoint(key, log_length),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_content_size() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let first = content_digest("first");
+        let second = content_digest("second");
+        let (record_id, record) = package_init_record(&key, &[&first, &second]);
+
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&first, &second]),
+            )
+            .await
+            .unwrap();
+
+        assert!(!store
+            .set_content_present(&log_id, &record_id, &first, Some(10))
+            .await
+            .unwrap());
+        assert_eq!(
+            store.get_content_size(&log_id, &record_id).await.unwrap(),
+            10
+        );
+
+        assert!(store
+            .set_content_present(&log_id, &record_id, &second, Some(32))
+            .await
+            .unwrap());
+        assert_eq!(
+            store.get_content_size(&log_id, &record_id).await.unwrap(),
+            42
+        );
+
+        for digest in [&first, &second] {
+            store
+                .set_content_validated(&log_id, &record_id, digest)
+                .await
+                .unwrap();
+        }
+        store
+            .commit_package_record(&log_id, &record_id, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_content_size(&log_id, &record_id).await.unwrap(),
+            42
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_operator_record_signature() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+        let (_, record) = operator_init_record(&key, &[]);
+
+        store
+            .verify_operator_record_signature(&log_id, &record)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_operator_record_signature_tampered() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (_, other_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        // The init entry claims `key`, but the envelope is signed by `other_key`
+        let (_, record) = operator_record(
+            &other_key,
+            None,
+            vec![OperatorEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: key.public_key(),
+            }],
+        );
+
+        assert!(matches!(
+            store
+                .verify_operator_record_signature(&log_id, &record)
+                .await,
+            Err(DataStoreError::SignatureVerificationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_package_records_by_id() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let log_id = package.0.clone();
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        let (second_id, second) = package_record(&key, Some(first_id.clone()), vec![]);
+        let (third_id, third) = package_record(&key, Some(second_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &first_id, &first, 0).await;
+        store_and_commit_package(&store, &package, &second_id, &second, 1).await;
+        store_and_commit_package(&store, &package, &third_id, &third, 2).await;
+
+        let records = store
+            .get_package_records_by_id(
+                &log_id,
+                &[third_id.clone(), first_id.clone(), second_id.clone()],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| r.envelope.clone())
+                .collect::<Vec<_>>(),
+            vec![third, first, second]
+        );
+        assert_eq!(
+            records.iter().map(|r| r.registry_index).collect::<Vec<_>>(),
+            vec![Some(2), Some(0), Some(1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_package_records_by_id_missing() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let (record_id, record) = package_init_record(&key, &[]);
+        let (missing_id, _) = package_record(&key, Some(record_id.clone()), vec![]);
+
+        store
+            .store_package_record(&log_id, &name, &record_id, &record, &IndexSet::new())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            store
+                .get_package_records_by_id(&log_id, &[record_id, missing_id.clone()])
+                .await,
+            Err(DataStoreError::RecordNotFound(id)) if id == missing_id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_log_leafs_starting_past_end() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (record_id, record) = operator_init_record(&key, &[]);
+        store_and_commit_operator(&store, &record_id, &record, 0).await;
+
+        assert_eq!(
+            store
+                .get_log_leafs_starting_with_registry_index(0, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(store
+            .get_log_leafs_starting_with_registry_index(1, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(store
+            .get_log_leafs_starting_with_registry_index(5, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_package_records_since_index() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        // Interleave two package logs so that `test:package` has gaps in its indices
+        let package = test_package();
+        let other = named_package("test:other");
+        let (first_id, first) = package_init_record(&key, &[]);
+        let (second_id, second) = package_record(&key, Some(first_id.clone()), vec![]);
+        let (third_id, third) = package_record(&key, Some(second_id.clone()), vec![]);
+        let (other_id, other_record) = package_init_record(&key, &[]);
+        let (next_other_id, next_other) = package_record(&key, Some(other_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+        store_and_commit_package(&store, &other, &other_id, &other_record, 2).await;
+        store_and_commit_package(&store, &package, &second_id, &second, 3).await;
+        store_and_commit_package(&store, &other, &next_other_id, &next_other, 4).await;
+        store_and_commit_package(&store, &package, &third_id, &third, 5).await;
+        store_checkpoint(&store, &operator_key, 6).await;
+
+        let indices = |records: Vec<PublishedProtoEnvelope<package::PackageRecord>>| {
+            records
+                .into_iter()
+                .map(|r| r.registry_index)
+                .collect::<Vec<_>>()
+        };
+
+        let records = store
+            .get_package_records_since_index(&package.0, 6, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(indices(records), vec![1, 3, 5]);
+
+        // Exact match starts after the matching entry
+        let records = store
+            .get_package_records_since_index(&package.0, 6, Some(3), 10)
+            .await
+            .unwrap();
+        assert_eq!(indices(records), vec![5]);
+
+        // Between entries starts with the next greater registry index
+        let records = store
+            .get_package_records_since_index(&package.0, 6, Some(2), 10)
+            .await
+            .unwrap();
+        assert_eq!(indices(records), vec![3, 5]);
+
+        let records = store
+            .get_package_records_since_index(&package.0, 6, Some(5), 10)
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+    }
+
+    #[derive(Default)]
+    struct CountingMetrics {
+        operator_stored: AtomicUsize,
+        package_stored: AtomicUsize,
+        operator_committed: AtomicUsize,
+        package_committed: AtomicUsize,
+        package_rejected: AtomicUsize,
+    }
+
+    impl DataStoreMetrics for CountingMetrics {
+        fn on_store(&self, kind: LogKind) {
+            match kind {
+                LogKind::Operator => &self.operator_stored,
+                LogKind::Package => &self.package_stored,
+            }
+            .fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_commit(&self, kind: LogKind) {
+            match kind {
+                LogKind::Operator => &self.operator_committed,
+                LogKind::Package => &self.package_committed,
+            }
+            .fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_reject(&self, kind: LogKind) {
+            assert_eq!(kind, LogKind::Package);
+            self.package_rejected.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let metrics = Arc::new(CountingMetrics::default());
+        let store = MemoryDataStore::new().with_metrics(metrics.clone());
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+
+        // Explicitly rejected
+        let (second_id, second) = package_record(&key, Some(first_id.clone()), vec![]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &second_id,
+                &second,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .reject_package_record(&package.0, &second_id, "rejected")
+            .await
+            .unwrap();
+
+        // Rejected by failing validation on commit
+        let (third_id, third) = package_init_record(&key, &[]);
+        store
+            .store_package_record(&package.0, &package.1, &third_id, &third, &IndexSet::new())
+            .await
+            .unwrap();
+        store
+            .commit_package_record(&package.0, &third_id, 2)
+            .await
+            .unwrap_err();
+
+        assert_eq!(metrics.operator_stored.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.package_stored.load(Ordering::SeqCst), 3);
+        assert_eq!(metrics.operator_committed.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.package_committed.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.package_rejected.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_on_commit_hook() {
+        let invocations = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook_invocations = invocations.clone();
+        let store = MemoryDataStore::new().with_on_commit_hook(Arc::new(move |index| {
+            hook_invocations.lock().unwrap().push(index)
+        }));
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+
+        // A second init record fails validation and must not invoke the hook
+        let (second_id, second) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &second_id,
+                &second,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .commit_package_record(&package.0, &second_id, 2)
+            .await
+            .unwrap_err();
+
+        assert_eq!(*invocations.lock().unwrap(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_current_registry_length() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        assert_eq!(store.current_registry_length().await.unwrap(), 0);
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        assert_eq!(store.current_registry_length().await.unwrap(), 2);
+        assert!(matches!(
+            store.get_latest_checkpoint().await,
+            Err(DataStoreError::NoCheckpoint)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_commit_registry_index_too_large() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        let log_id = LogId::operator_log::<Sha256>();
+
+        store
+            .store_operator_record(&log_id, &operator_id, &operator)
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.commit_operator_record(&log_id, &operator_id, 1).await,
+            Err(DataStoreError::NonContiguousRegistryIndex {
+                expected: 0,
+                got: 1
+            })
+        ));
+
+        // The record is still pending and can be committed at the next index
+        assert_eq!(store.current_registry_length().await.unwrap(), 0);
+        store
+            .commit_operator_record(&log_id, &operator_id, 0)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commit_registry_index_collision() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.commit_package_record(&package.0, &record_id, 0).await,
+            Err(DataStoreError::NonContiguousRegistryIndex {
+                expected: 1,
+                got: 0
+            })
+        ));
+
+        let leafs = store
+            .get_log_leafs_starting_with_registry_index(0, 10)
+            .await
+            .unwrap();
+        assert_eq!(leafs.len(), 1);
+        assert_eq!(leafs[0].1.record_id, operator_id);
+        store
+            .commit_package_record(&package.0, &record_id, 1)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_content_size_mismatch() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let digest = content_digest("content");
+        let first = named_package("test:first");
+        let second = named_package("test:second");
+
+        let (first_id, first_record) = package_init_record(&key, &[&digest]);
+        let (second_id, second_record) = package_init_record(&key, &[&digest]);
+        let missing = IndexSet::from([&digest]);
+        store
+            .store_package_record(&first.0, &first.1, &first_id, &first_record, &missing)
+            .await
+            .unwrap();
+        store
+            .store_package_record(&second.0, &second.1, &second_id, &second_record, &missing)
+            .await
+            .unwrap();
+
+        store
+            .set_content_present(&first.0, &first_id, &digest, Some(7))
+            .await
+            .unwrap();
+        match store
+            .set_content_present(&second.0, &second_id, &digest, Some(8))
+            .await
+        {
+            Err(DataStoreError::ContentSizeMismatch {
+                digest: d,
+                expected: 7,
+                got: 8,
+            }) => assert_eq!(d, digest),
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        // The content is still missing for the second record
+        assert!(store
+            .is_content_missing(&second.0, &second_id, &digest)
+            .await
+            .unwrap());
+
+        // Sizes are recorded per record, even for shared content
+        assert_eq!(
+            store.get_content_size(&second.0, &second_id).await.unwrap(),
+            0
+        );
+        store
+            .set_content_present(&second.0, &second_id, &digest, Some(7))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_content_size(&second.0, &second_id).await.unwrap(),
+            7
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_records() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let validated = named_package("test:validated");
+        let rejected = named_package("test:rejected");
+        let pending = named_package("test:pending");
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (validated_id, validated_record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &validated, &validated_id, &validated_record, 1).await;
+
+        let (rejected_id, rejected_record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &rejected.0,
+                &rejected.1,
+                &rejected_id,
+                &rejected_record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .reject_package_record(&rejected.0, &rejected_id, "rejected")
+            .await
+            .unwrap();
+
+        let (pending_id, pending_record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &pending.0,
+                &pending.1,
+                &pending_id,
+                &pending_record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get_pending_records().await.unwrap(),
+            vec![(pending.0, pending_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_all_validated_records_from() {
+        use futures::TryStreamExt;
+
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let mut record_ids = Vec::new();
+        for i in 1..5 {
+            let package = named_package(&format!("test:package{i}"));
+            let (record_id, record) = package_init_record(&key, &[]);
+            store_and_commit_package(&store, &package, &record_id, &record, i).await;
+            record_ids.push(record_id);
+        }
+
+        let leafs = store
+            .get_all_validated_records_from(3)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(leafs.len(), 2);
+        assert_eq!(leafs[0].record_id, record_ids[2]);
+        assert_eq!(leafs[1].record_id, record_ids[3]);
+
+        // Streaming from the start is the same as streaming all records
+        let all = store
+            .get_all_validated_records()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let from_start = store
+            .get_all_validated_records_from(0)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 5);
+        assert_eq!(all, from_start);
+    }
+
+    #[tokio::test]
+    async fn test_verify_log_leaf() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        let leaf = LogLeaf {
+            log_id: package.0.clone(),
+            record_id: record_id.clone(),
+        };
+        assert!(store.verify_log_leaf(1, &leaf).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_log_leaf_mismatch() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        // Correct record id but the wrong log id
+        let leaf = LogLeaf {
+            log_id: LogId::operator_log::<Sha256>(),
+            record_id: record_id.clone(),
+        };
+        assert!(!store.verify_log_leaf(1, &leaf).await.unwrap());
+
+        // Correct log id but the wrong record id
+        let leaf = LogLeaf {
+            log_id: package.0.clone(),
+            record_id: operator_id,
+        };
+        assert!(!store.verify_log_leaf(1, &leaf).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_log_leaf_missing() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let leaf = LogLeaf {
+            log_id: LogId::operator_log::<Sha256>(),
+            record_id: operator_id,
+        };
+        assert!(matches!(
+            store.verify_log_leaf(1, &leaf).await,
+            Err(DataStoreError::LogLeafNotFound(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_record_registry_index() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+
+        assert_eq!(
+            store
+                .get_record_registry_index(&package.0, &first_id)
+                .await
+                .unwrap(),
+            Some(1)
+        );
+
+        let (second_id, second) = package_record(&key, Some(first_id), vec![]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &second_id,
+                &second,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .get_record_registry_index(&package.0, &second_id)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(matches!(
+            store
+                .get_record_registry_index(&package.0, &operator_id)
+                .await,
+            Err(DataStoreError::RecordNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replay_ops() {
+        let store = MemoryDataStore::new().with_record_ops(true);
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let digest = content_digest("content");
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first) = package_init_record(&key, &[&digest]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &first_id,
+                &first,
+                &IndexSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+        store
+            .set_content_present(&package.0, &first_id, &digest, Some(7))
+            .await
+            .unwrap();
+        store
+            .set_content_validated(&package.0, &first_id, &digest)
+            .await
+            .unwrap();
+        store
+            .commit_package_record(&package.0, &first_id, 1)
+            .await
+            .unwrap();
+        store_checkpoint(&store, &operator_key, 2).await;
+
+        // A record that fails validation on commit
+        let (second_id, second) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &second_id,
+                &second,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .commit_package_record(&package.0, &second_id, 2)
+            .await
+            .unwrap_err();
+
+        let ops = store.recorded_ops();
+        assert_eq!(ops.len(), 9);
+
+        let replayed = MemoryDataStore::replay_ops(&ops);
+        assert!(*store.state.read().await == *replayed.state.read().await);
+    }
+
+    #[tokio::test]
+    async fn test_find_operator_for_namespace() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        assert_eq!(
+            store.find_operator_for_namespace("test").await.unwrap(),
+            Some(LogId::operator_log::<Sha256>())
+        );
+        assert_eq!(
+            store.find_operator_for_namespace("other").await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_operator_for_namespace_multiple() {
+        let store = MemoryDataStore::new();
+        let (_, first_key) = generate_p256_pair();
+        let (_, second_key) = generate_p256_pair();
+        let first_log_id = LogId::operator_log::<Sha256>();
+        let second_log_id: LogId = content_digest("second operator").into();
+
+        let (first_id, first) = operator_init_record(&first_key, &["test"]);
+        store_and_commit_operator(&store, &first_id, &first, 0).await;
+
+        let (second_id, second) = operator_init_record(&second_key, &["test"]);
+        store
+            .store_operator_record(&second_log_id, &second_id, &second)
+            .await
+            .unwrap();
+        store
+            .commit_operator_record(&second_log_id, &second_id, 1)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.find_operator_for_namespace("test").await.unwrap(),
+            Some(first_log_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_latest_checkpoint_with_key() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+
+        assert!(matches!(
+            store.get_latest_checkpoint_with_key().await,
+            Err(DataStoreError::NoCheckpoint)
+        ));
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        store_checkpoint(&store, &operator_key, 1).await;
+
+        let (checkpoint, key_id) = store.get_latest_checkpoint_with_key().await.unwrap();
+        assert_eq!(&key_id, checkpoint.key_id());
+        assert_eq!(key_id, operator_key.public_key().fingerprint());
+    }
+
+    #[tokio::test]
+    async fn test_get_leaf_refs_starting_with() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        for i in 1..4 {
+            let package = named_package(&format!("test:package{i}"));
+            let (record_id, record) = package_init_record(&key, &[]);
+            store_and_commit_package(&store, &package, &record_id, &record, i).await;
+        }
+
+        let leafs = store
+            .get_log_leafs_starting_with_registry_index(1, 2)
+            .await
+            .unwrap();
+        let refs = store.get_leaf_refs_starting_with(1, 2).await.unwrap();
+        assert_eq!(refs.len(), 2);
+        for ((index, leaf), (ref_index, log_id, record_id)) in leafs.iter().zip(&refs) {
+            assert_eq!(index, ref_index);
+            assert_eq!(&leaf.log_id, log_id.as_ref());
+            assert_eq!(&leaf.record_id, record_id.as_ref());
+        }
+
+        assert!(store
+            .get_leaf_refs_starting_with(4, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_checkpoint_at_time() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+
+        for (log_length, secs) in [(1, 100), (2, 200), (3, 300)] {
+            store
+                .store_checkpoint(
+                    &content_digest(&format!("checkpoint-{log_length}")),
+                    checkpoint_at(&operator_key, log_length, at(secs)),
+                )
+                .await
+                .unwrap();
+        }
+
+        let log_length_at = |checkpoint: Option<SerdeEnvelope<TimestampedCheckpoint>>| {
+            checkpoint.map(|c| c.as_ref().checkpoint.log_length)
+        };
+        assert_eq!(
+            log_length_at(store.get_checkpoint_at_time(at(50)).await.unwrap()),
+            None
+        );
+        assert_eq!(
+            log_length_at(store.get_checkpoint_at_time(at(100)).await.unwrap()),
+            Some(1)
+        );
+        assert_eq!(
+            log_length_at(store.get_checkpoint_at_time(at(250)).await.unwrap()),
+            Some(2)
+        );
+        assert_eq!(
+            log_length_at(store.get_checkpoint_at_time(at(1000)).await.unwrap()),
+            Some(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_close() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+
+        store.close();
+
+        let (second_id, second) = package_record(&key, Some(first_id.clone()), vec![]);
+        assert!(matches!(
+            store
+                .store_package_record(
+                    &package.0,
+                    &package.1,
+                    &second_id,
+                    &second,
+                    &IndexSet::new()
+                )
+                .await,
+            Err(DataStoreError::StoreClosed)
+        ));
+
+        let record = store
+            .get_package_record(&package.0, &first_id)
+            .await
+            .unwrap();
+        assert_eq!(record.registry_index, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_get_records_awaiting_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let first_digest = content_digest("first");
+        let second_digest = content_digest("second");
+        let third_digest = content_digest("third");
+
+        assert!(matches!(
+            store.get_records_awaiting_content(&package.0).await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+
+        let mut record_ids = Vec::new();
+        for digest in [&first_digest, &second_digest, &third_digest] {
+            let (record_id, record) = package_init_record(&key, &[digest]);
+            store
+                .store_package_record(
+                    &package.0,
+                    &package.1,
+                    &record_id,
+                    &record,
+                    &IndexSet::from([digest]),
+                )
+                .await
+                .unwrap();
+            record_ids.push(record_id);
+        }
+
+        // Satisfy the content of the last record
+        store
+            .set_content_present(&package.0, &record_ids[2], &third_digest, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .get_records_awaiting_content(&package.0)
+                .await
+                .unwrap(),
+            vec![
+                (record_ids[0].clone(), IndexSet::from([first_digest])),
+                (record_ids[1].clone(), IndexSet::from([second_digest])),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_timestamped_checkpoints() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let checkpoints = [checkpoint(&operator_key, 1), checkpoint(&operator_key, 2)];
+        store
+            .verify_timestamped_checkpoints(&log_id, &checkpoints)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_timestamped_checkpoints_tampered() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        // The second checkpoint carries the signature of the first
+        let first = checkpoint(&operator_key, 1);
+        let second = checkpoint(&operator_key, 2);
+        let tampered = SerdeEnvelope::from_parts_unchecked(
+            second.as_ref().clone(),
+            second.key_id().clone(),
+            first.signature().clone(),
+        );
+
+        assert!(matches!(
+            store
+                .verify_timestamped_checkpoints(&log_id, &[first, tampered])
+                .await,
+            Err(DataStoreError::CheckpointSignatureFailed { index: 1 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_imported_namespace_source() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) = operator_record(
+            &operator_key,
+            None,
+            vec![
+                OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: operator_key.public_key(),
+                },
+                OperatorEntry::DefineNamespace {
+                    namespace: "defined".to_string(),
+                },
+                OperatorEntry::ImportNamespace {
+                    namespace: "imported".to_string(),
+                    registry: "registry.example.com".to_string(),
+                },
+            ],
+        );
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        assert_eq!(
+            store
+                .get_imported_namespace_source(&log_id, "imported")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("registry.example.com")
+        );
+        assert_eq!(
+            store
+                .get_imported_namespace_source(&log_id, "defined")
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            store
+                .get_imported_namespace_source(&log_id, "undefined")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_record_history() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .reject_package_record(&package.0, &record_id, "bad record")
+            .await
+            .unwrap();
+
+        let history = store
+            .get_record_history(&package.0, &record_id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].1, "pending");
+        assert_eq!(history[1].1, "rejected: bad record");
+        assert!(history[0].0 <= history[1].0);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate_package_names() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let other = named_package("test:other");
+
+        for (log_id, name) in [&package, &other] {
+            let (record_id, record) = package_init_record(&key, &[]);
+            store
+                .store_package_record(log_id, name, &record_id, &record, &IndexSet::new())
+                .await
+                .unwrap();
+        }
+
+        assert!(store
+            .find_duplicate_package_names()
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Inject a second log for the same package name
+        let duplicate: LogId = content_digest("duplicate").into();
+        store
+            .state
+            .write()
+            .await
+            .package_names
+            .insert(duplicate.clone(), Some(package.1.clone()));
+
+        assert_eq!(
+            store.find_duplicate_package_names().await.unwrap(),
+            vec![(package.1, vec![package.0, duplicate])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_checkpoint_before_and_after() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        for log_length in [2, 4, 6] {
+            store_checkpoint(&store, &operator_key, log_length).await;
+        }
+
+        let log_length = |checkpoint: Option<SerdeEnvelope<TimestampedCheckpoint>>| {
+            checkpoint.map(|c| c.as_ref().checkpoint.log_length)
+        };
+
+        assert_eq!(
+            log_length(store.get_checkpoint_before(2).await.unwrap()),
+            None
+        );
+        assert_eq!(
+            log_length(store.get_checkpoint_before(4).await.unwrap()),
+            Some(2)
+        );
+        assert_eq!(
+            log_length(store.get_checkpoint_before(7).await.unwrap()),
+            Some(6)
+        );
+
+        assert_eq!(
+            log_length(store.get_checkpoint_after(6).await.unwrap()),
+            None
+        );
+        assert_eq!(
+            log_length(store.get_checkpoint_after(4).await.unwrap()),
+            Some(6)
+        );
+        assert_eq!(
+            log_length(store.get_checkpoint_after(0).await.unwrap()),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_and_commit_operator_record() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (record_id, record) = operator_init_record(&operator_key, &["test"]);
+        store
+            .store_and_commit_operator_record(&log_id, &record_id, &record, 0)
+            .await
+            .unwrap();
+
+        let record = store
+            .get_operator_record(&log_id, &record_id)
+            .await
+            .unwrap();
+        assert_eq!(record.status, crate::datastore::RecordStatus::Validated);
+        assert_eq!(store.current_registry_length().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_store_and_commit_operator_record_invalid() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        // A record that does not start with an init entry
+        let (record_id, record) = operator_record(
+            &operator_key,
+            None,
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "test".to_string(),
+            }],
+        );
+        assert!(matches!(
+            store
+                .store_and_commit_operator_record(&log_id, &record_id, &record, 0)
+                .await,
+            Err(DataStoreError::OperatorValidationFailed(_))
+        ));
+
+        let record = store
+            .get_operator_record(&log_id, &record_id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            record.status,
+            crate::datastore::RecordStatus::Rejected(_)
+        ));
+        assert_eq!(store.current_registry_length().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_store_and_commit_operator_record_non_contiguous() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (record_id, record) = operator_init_record(&operator_key, &["test"]);
+        assert!(matches!(
+            store
+                .store_and_commit_operator_record(&log_id, &record_id, &record, 1)
+                .await,
+            Err(DataStoreError::NonContiguousRegistryIndex {
+                expected: 0,
+                got: 1
+            })
+        ));
+
+        // The record was not stored, so it can be stored again
+        assert!(store
+            .get_operator_record(&log_id, &record_id)
+            .await
+            .is_err());
+        assert!(store.get_pending_records().await.unwrap().is_empty());
+        store
+            .store_and_commit_operator_record(&log_id, &record_id, &record, 0)
+            .await
+            .unwrap();
+        assert_eq!(store.current_registry_length().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_package_records_paged() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (mut prev_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &prev_id, &record, 1).await;
+        for registry_index in 2..6 {
+            let (record_id, record) = package_record(&key, Some(prev_id), vec![]);
+            store_and_commit_package(&store, &package, &record_id, &record, registry_index).await;
+            prev_id = record_id;
+        }
+        store_checkpoint(&store, &operator_key, 6).await;
+
+        let mut cursor = None;
+        let mut pages = Vec::new();
+        loop {
+            let (records, next) = store
+                .get_package_records_paged(&package.0, cursor, 2)
+                .await
+                .unwrap();
+            pages.push(records.iter().map(|r| r.registry_index).collect::<Vec<_>>());
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, vec![vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[tokio::test]
+    async fn test_get_package_records_paged_corrupted_cursor() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+        let (second_id, second) = package_record(&key, Some(first_id), vec![]);
+        store_and_commit_package(&store, &package, &second_id, &second, 2).await;
+        store_checkpoint(&store, &operator_key, 3).await;
+
+        let (_, cursor) = store
+            .get_package_records_paged(&package.0, None, 1)
+            .await
+            .unwrap();
+        let cursor = cursor.unwrap();
+
+        // Point the cursor at a different index without updating the checksum
+        let (_, checksum) = cursor.as_str().split_once('.').unwrap();
+        let corrupted = PageCursor::from(format!("0.{checksum}"));
+
+        for cursor in [corrupted, PageCursor::from("not a cursor".to_string())] {
+            assert!(matches!(
+                store
+                    .get_package_records_paged(&package.0, Some(cursor), 1)
+                    .await,
+                Err(DataStoreError::InvalidCursor)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_package_exists() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let published = named_package("test:published");
+        let pending = named_package("test:pending");
+        let unknown = named_package("test:unknown");
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &published, &record_id, &record, 1).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &pending.0,
+                &pending.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+
+        assert!(store.package_exists(&published.0).await.unwrap());
+        assert!(!store.package_exists(&pending.0).await.unwrap());
+        assert!(!store.package_exists(&unknown.0).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_log_leafs() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        let expected = store
+            .get_log_leafs_with_registry_index(&[0, 1])
+            .await
+            .unwrap();
+        let root = store.get_current_root().await.unwrap();
+
+        // Corrupt the leafs and the log accumulated over them
+        {
+            let mut state = store.state.write().await;
+            state.log_leafs.swap_remove(&0);
+            state.leaf_accumulator = LeafAccumulator::default();
+        }
+        assert!(matches!(
+            store.get_log_leafs_with_registry_index(&[0, 1]).await,
+            Err(DataStoreError::LogLeafNotFound(0))
+        ));
+
+        assert_eq!(store.rebuild_log_leafs().await.unwrap(), 2);
+        assert_eq!(
+            store
+                .get_log_leafs_with_registry_index(&[0, 1])
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(store.get_current_root().await.unwrap(), root);
+        let (log, _) = store.state.read().await.verifiable_log::<Sha256>(2);
+        assert_eq!(root, log.checkpoint().root().into());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_log_leafs_gap() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&operator_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        // Move the only committed record past a gap
+        let log_id = LogId::operator_log::<Sha256>();
+        if let RecordStatus::Validated(r) = store
+            .state
+            .write()
+            .await
+            .records
+            .get_mut(&log_id)
+            .unwrap()
+            .get_mut(&operator_id)
+            .unwrap()
+        {
+            r.registry_index = 1;
+        }
+
+        assert!(matches!(
+            store.rebuild_log_leafs().await,
+            Err(DataStoreError::LogLeafNotFound(0))
+        ));
+        assert_eq!(store.current_registry_length().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_count() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let first = content_digest("first");
+        let second = content_digest("second");
+
+        let (record_id, record) = package_init_record(&key, &[&first, &second]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::from([&first, &second]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .missing_content_count(&package.0, &record_id)
+                .await
+                .unwrap(),
+            2
+        );
+        for (digest, expected) in [(&first, 1), (&second, 0)] {
+            store
+                .set_content_present(&package.0, &record_id, digest, None)
+                .await
+                .unwrap();
+            assert_eq!(
+                store
+                    .missing_content_count(&package.0, &record_id)
+                    .await
+                    .unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_can_publish_package_suggests_namespaces() {
+        let store = MemoryDataStore::new();
+        let (_, operator_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) =
+            operator_init_record(&operator_key, &["foobar", "other", "unrelated"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (_, name) = named_package("foobaz:package");
+        let err = store
+            .verify_can_publish_package(&log_id, &name)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("did you mean `foobar`"));
+        match err {
+            DataStoreError::PackageNamespaceNotDefined {
+                namespace,
+                suggestions,
+            } => {
+                assert_eq!(namespace, "foobaz");
+                assert_eq!(suggestions.len(), 3);
+                assert_eq!(suggestions[0], "foobar");
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_operator_records() {
+        use futures::TryStreamExt;
+
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (init_id, init) = operator_init_record(&key, &["a"]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+        let mut prev = init_id.clone();
+        for (i, namespace) in ["b", "c", "d"].into_iter().enumerate() {
+            let (id, record) = operator_record(
+                &key,
+                Some(prev),
+                vec![OperatorEntry::DefineNamespace {
+                    namespace: namespace.to_string(),
+                }],
+            );
+            store_and_commit_operator(&store, &id, &record, i + 1).await;
+            prev = id;
+        }
+        store_checkpoint(&store, &key, 3).await;
+
+        for since in [None, Some(&init_id)] {
+            let streamed: Vec<_> = store
+                .stream_operator_records(&log_id, 3, since)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            let paged = store
+                .get_operator_records(&log_id, 3, since, u16::MAX)
+                .await
+                .unwrap();
+            assert_eq!(streamed, paged);
+        }
+
+        let streamed: Vec<_> = store
+            .stream_operator_records(&log_id, 3, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed.len(), 3);
+
+        assert!(matches!(
+            store.stream_operator_records(&log_id, 2, None).await,
+            Err(DataStoreError::CheckpointNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_content_progress() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let first = content_digest("first");
+        let second = content_digest("second");
+        let (record_id, record) = package_init_record(&key, &[&first, &second]);
+
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&first, &second]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(store.get_content_progress(&first).await.unwrap(), None);
+
+        store.record_content_progress(&first, 10).await.unwrap();
+        store.record_content_progress(&second, 5).await.unwrap();
+        assert_eq!(store.get_content_progress(&first).await.unwrap(), Some(10));
+
+        store.record_content_progress(&first, 20).await.unwrap();
+        assert_eq!(store.get_content_progress(&first).await.unwrap(), Some(20));
+
+        store
+            .set_content_present(&log_id, &record_id, &first, Some(32))
+            .await
+            .unwrap();
+        assert_eq!(store.get_content_progress(&first).await.unwrap(), None);
+        assert_eq!(store.get_content_progress(&second).await.unwrap(), Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_get_store_stats() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+
+        assert_eq!(
+            store.get_store_stats().await.unwrap(),
+            StoreStats::default()
+        );
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let package = test_package();
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        let (pending_log_id, pending_name) = named_package("test:pending");
+        let digest = content_digest("pending");
+        let (pending_id, pending) = package_init_record(&key, &[&digest]);
+        store
+            .store_package_record(
+                &pending_log_id,
+                &pending_name,
+                &pending_id,
+                &pending,
+                &IndexSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+
+        let (rejected_id, rejected) = operator_record(&key, Some(operator_id), vec![]);
+        store
+            .store_operator_record(&operator_log_id, &rejected_id, &rejected)
+            .await
+            .unwrap();
+        store
+            .reject_operator_record(&operator_log_id, &rejected_id, "rejected")
+            .await
+            .unwrap();
+
+        store_checkpoint(&store, &key, 1).await;
+        store_checkpoint(&store, &key, 2).await;
+
+        assert_eq!(
+            store.get_store_stats().await.unwrap(),
+            StoreStats {
+                operator_logs: 1,
+                package_logs: 2,
+                validated_records: 2,
+                pending_records: 1,
+                rejected_records: 1,
+                checkpoints: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_package_records_since_behavior() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+
+        let (rejected_id, rejected) = package_record(&key, Some(init_id), vec![]);
+        store
+            .store_package_record(log_id, name, &rejected_id, &rejected, &IndexSet::new())
+            .await
+            .unwrap();
+        store
+            .reject_package_record(log_id, &rejected_id, "rejected")
+            .await
+            .unwrap();
+        store_checkpoint(&store, &key, 2).await;
+
+        let records = store
+            .get_package_records_with_since_behavior(
+                log_id,
+                2,
+                Some(&rejected_id),
+                u16::MAX,
+                SinceBehavior::Lenient,
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].registry_index, 1);
+        assert_eq!(
+            records,
+            store
+                .get_package_records(log_id, 2, Some(&rejected_id), u16::MAX)
+                .await
+                .unwrap()
+        );
+
+        match store
+            .get_package_records_with_since_behavior(
+                log_id,
+                2,
+                Some(&rejected_id),
+                u16::MAX,
+                SinceBehavior::Error,
+            )
+            .await
+        {
+            Err(DataStoreError::InvalidSinceRecord(id)) => assert_eq!(id, rejected_id),
+            r => panic!("unexpected result: {r:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_checkpoint_signing_keys() {
+        let store = MemoryDataStore::new();
+        let (_, alice) = generate_p256_pair();
+        let (bob_pub, _) = generate_p256_pair();
+        let (carol_pub, _) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (init_id, init) = operator_init_record(&alice, &[]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+
+        let (grant_id, grant) = operator_record(
+            &alice,
+            Some(init_id),
+            vec![
+                OperatorEntry::GrantFlat {
+                    key: bob_pub.clone(),
+                    permissions: vec![operator::Permission::Commit],
+                },
+                OperatorEntry::GrantFlat {
+                    key: carol_pub.clone(),
+                    permissions: vec![operator::Permission::DefineNamespace],
+                },
+            ],
+        );
+        store_and_commit_operator(&store, &grant_id, &grant, 1).await;
+
+        assert_eq!(
+            store.get_checkpoint_signing_keys(&log_id).await.unwrap(),
+            vec![alice.public_key().fingerprint(), bob_pub.fingerprint()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_init_record() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        assert!(matches!(
+            store.get_init_record(log_id).await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store
+            .store_package_record(log_id, name, &init_id, &init, &IndexSet::new())
+            .await
+            .unwrap();
+        match store.get_init_record(log_id).await {
+            Err(DataStoreError::RecordNotFound(id)) => assert_eq!(id, init_id),
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("expected the pending init record to not be found"),
+        }
+
+        store
+            .commit_package_record(log_id, &init_id, 1)
+            .await
+            .unwrap();
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+
+        let leafs = store
+            .get_log_leafs_starting_with_registry_index(0, 10)
+            .await
+            .unwrap();
+        let first_leaf = |log_id: &LogId| {
+            leafs
+                .iter()
+                .find(|(_, leaf)| &leaf.log_id == log_id)
+                .map(|(index, _)| *index)
+        };
+
+        let record = store.get_init_record(log_id).await.unwrap();
+        assert_eq!(record.envelope, init);
+        assert_eq!(record.registry_index, first_leaf(log_id));
+
+        let record = store
+            .get_operator_init_record(&operator_log_id)
+            .await
+            .unwrap();
+        assert_eq!(record.envelope, operator);
+        assert_eq!(record.registry_index, first_leaf(&operator_log_id));
+    }
+
+    #[tokio::test]
+    async fn test_unset_package_name() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+        store_checkpoint(&store, &key, 2).await;
+
+        assert_eq!(
+            store.get_log_id_for_package(name).await.unwrap(),
+            Some(log_id.clone())
+        );
+
+        store.unset_package_name(log_id).await.unwrap();
+
+        assert_eq!(store.get_log_id_for_package(name).await.unwrap(), None);
+        assert_eq!(
+            store
+                .get_package_names(std::slice::from_ref(log_id))
+                .await
+                .unwrap()
+                .get(log_id),
+            Some(&None)
+        );
+
+        let records = store
+            .get_package_records(log_id, 2, None, u16::MAX)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].envelope, record);
+
+        assert!(matches!(
+            store
+                .unset_package_name(&LogId::operator_log::<Sha256>())
+                .await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_stale_pending_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let digest = content_digest("missing");
+        let (record_id, record) = package_init_record(&key, &[&digest]);
+
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .get_stale_pending_content(Duration::ZERO)
+                .await
+                .unwrap(),
+            vec![(
+                log_id.clone(),
+                record_id.clone(),
+                IndexSet::from([digest.clone()])
+            )]
+        );
+        assert!(store
+            .get_stale_pending_content(Duration::from_secs(3600))
+            .await
+            .unwrap()
+            .is_empty());
+
+        store
+            .set_content_present(&log_id, &record_id, &digest, None)
+            .await
+            .unwrap();
+        assert!(store
+            .get_stale_pending_content(Duration::ZERO)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recommit_is_idempotent() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let commits = Arc::new(AtomicUsize::new(0));
+        let store = store.with_on_commit_hook({
+            let commits = commits.clone();
+            Arc::new(move |_| {
+                commits.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        // Retrying either commit at the same index succeeds without another leaf
+        store
+            .commit_operator_record(&LogId::operator_log::<Sha256>(), &operator_id, 0)
+            .await
+            .unwrap();
+        store
+            .commit_package_record(&package.0, &record_id, 1)
+            .await
+            .unwrap();
+
+        assert_eq!(store.current_registry_length().await.unwrap(), 2);
+        assert_eq!(commits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recommit_at_different_index() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        assert!(matches!(
+            store.commit_package_record(&package.0, &record_id, 2).await,
+            Err(DataStoreError::RecordAlreadyCommitted { at: 1 })
+        ));
+        assert_eq!(store.current_registry_length().await.unwrap(), 2);
+        assert_eq!(
+            store
+                .get_record_registry_index(&package.0, &record_id)
+                .await
+                .unwrap(),
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_rejected_records() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let (log_id, name) = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (rejected_operator_id, rejected_operator) =
+            operator_record(&key, Some(operator_id), vec![]);
+        store
+            .store_operator_record(&operator_log_id, &rejected_operator_id, &rejected_operator)
+            .await
+            .unwrap();
+        store
+            .reject_operator_record(&operator_log_id, &rejected_operator_id, "bad operator")
+            .await
+            .unwrap();
+
+        let (rejected_package_id, rejected_package) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &rejected_package_id,
+                &rejected_package,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .reject_package_record(&log_id, &rejected_package_id, "bad package")
+            .await
+            .unwrap();
+
+        let rejected = store.get_rejected_records(10).await.unwrap();
+        assert_eq!(
+            rejected,
+            vec![
+                RejectedRecordInfo {
+                    log_id: operator_log_id,
+                    record_id: rejected_operator_id,
+                    kind: LogKind::Operator,
+                    reason: "bad operator".to_string(),
+                },
+                RejectedRecordInfo {
+                    log_id,
+                    record_id: rejected_package_id,
+                    kind: LogKind::Package,
+                    reason: "bad package".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(store.get_rejected_records(1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_max_record_bytes() {
+        let (_, key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let (log_id, name) = test_package();
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        let (record_id, record) = package_init_record(&key, &[]);
+
+        let max = operator.content_bytes().len();
+        let store = MemoryDataStore::new().with_max_record_bytes(max);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (large_id, large) = package_init_record(
+            &key,
+            &[
+                &content_digest("first"),
+                &content_digest("second"),
+                &content_digest("third"),
+            ],
+        );
+        assert!(large.content_bytes().len() > max);
+        match store
+            .store_package_record(&log_id, &name, &large_id, &large, &IndexSet::new())
+            .await
+        {
+            Err(DataStoreError::RecordTooLarge { size, max: limit }) => {
+                assert_eq!(size, large.content_bytes().len());
+                assert_eq!(limit, max);
+            }
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert!(matches!(
+            store.get_package_record(&log_id, &large_id).await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+
+        // Records within the limit are stored as usual
+        assert!(record.content_bytes().len() <= max);
+        store
+            .store_package_record(&log_id, &name, &record_id, &record, &IndexSet::new())
+            .await
+            .unwrap();
+
+        let store = MemoryDataStore::new().with_max_record_bytes(max - 1);
+        assert!(matches!(
+            store
+                .store_operator_record(&operator_log_id, &operator_id, &operator)
+                .await,
+            Err(DataStoreError::RecordTooLarge { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_head_record_id() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        assert!(matches!(
+            store.get_head_record_id(log_id).await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        assert_eq!(
+            store
+                .get_head_record_id(&LogId::operator_log::<Sha256>())
+                .await
+                .unwrap(),
+            Some(operator_id)
+        );
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store
+            .store_package_record(log_id, name, &init_id, &init, &IndexSet::new())
+            .await
+            .unwrap();
+        assert_eq!(store.get_head_record_id(log_id).await.unwrap(), None);
+
+        store
+            .commit_package_record(log_id, &init_id, 1)
+            .await
+            .unwrap();
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        store_checkpoint(&store, &key, 3).await;
+
+        let records = store
+            .get_package_records(log_id, 3, None, u16::MAX)
+            .await
+            .unwrap();
+        let last = records.last().unwrap();
+        assert_eq!(RecordId::package_record::<Sha256>(&last.envelope), next_id);
+        assert_eq!(
+            store.get_head_record_id(log_id).await.unwrap(),
+            Some(next_id)
+        );
+
+        store.state.write().await.log_leafs.shift_remove(&2);
+        assert!(matches!(
+            store.get_head_record_id(log_id).await,
+            Err(DataStoreError::LogLeafNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_content_present() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let first = content_digest("first");
+        let second = content_digest("second");
+        let (record_id, record) = package_init_record(&key, &[&first, &second]);
+
+        let mut events = store.subscribe_content_present();
+
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&first, &second]),
+            )
+            .await
+            .unwrap();
+        store
+            .set_content_present(&log_id, &record_id, &first, None)
+            .await
+            .unwrap();
+
+        // Marking the same content present again does not emit an event
+        store
+            .set_content_present(&log_id, &record_id, &first, None)
+            .await
+            .unwrap();
+        store
+            .set_content_present(&log_id, &record_id, &second, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events.recv().await.unwrap(),
+            (log_id.clone(), record_id.clone(), first)
+        );
+        assert_eq!(
+            events.recv().await.unwrap(),
+            (log_id.clone(), record_id.clone(), second)
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_revalidate_log() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let log_id = &package.0;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+
+        store.revalidate_log(log_id).await.unwrap();
+
+        // Corrupt the second record so that it no longer chains to the first
+        let (_, corrupted) = package_record(&key, Some(next_id.clone()), vec![]);
+        store
+            .state
+            .write()
+            .await
+            .packages
+            .get_mut(log_id)
+            .unwrap()
+            .entries[1]
+            .record_content = corrupted;
+
+        match store.revalidate_log(log_id).await {
+            Err(DataStoreError::RevalidationFailed { record_id, .. }) => {
+                assert_eq!(record_id, next_id)
+            }
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        // A missing leaf is reported rather than panicking
+        store.state.write().await.log_leafs.shift_remove(&2);
+        assert!(matches!(
+            store.revalidate_log(log_id).await,
+            Err(DataStoreError::LogLeafNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_package_names_lenient() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let (unknown_log_id, _) = named_package("test:unknown");
+        let (record_id, record) = package_init_record(&key, &[]);
+
+        store
+            .store_package_record(&log_id, &name, &record_id, &record, &IndexSet::new())
+            .await
+            .unwrap();
+
+        let log_ids = [unknown_log_id.clone(), log_id.clone()];
+        assert!(matches!(
+            store.get_package_names(&log_ids).await,
+            Err(DataStoreError::LogNotFound(id)) if id == unknown_log_id
+        ));
+        assert_eq!(
+            store.get_package_names_lenient(&log_ids).await.unwrap(),
+            IndexMap::from([(log_id, Some(name))])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_records_with_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (first_log_id, first_name) = named_package("test:first");
+        let (second_log_id, second_name) = named_package("test:second");
+        let shared = content_digest("shared");
+        let other = content_digest("other");
+
+        let (first_id, first) = package_init_record(&key, &[&shared]);
+        let (second_id, second) = package_init_record(&key, &[&shared, &other]);
+        store
+            .store_package_record(
+                &first_log_id,
+                &first_name,
+                &first_id,
+                &first,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        store
+            .store_package_record(
+                &second_log_id,
+                &second_name,
+                &second_id,
+                &second,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get_records_with_content(&shared).await.unwrap(),
+            vec![
+                (first_log_id, first_id),
+                (second_log_id.clone(), second_id.clone())
+            ]
+        );
+        assert_eq!(
+            store.get_records_with_content(&other).await.unwrap(),
+            vec![(second_log_id, second_id)]
+        );
+        assert!(store
+            .get_records_with_content(&content_digest("unknown"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_records_in_registry_order() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+
+        let (define_id, define) = operator_record(
+            &key,
+            Some(operator_id),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "other".to_string(),
+            }],
+        );
+        store_and_commit_operator(&store, &define_id, &define, 2).await;
+
+        let (next_id, next) = package_record(&key, Some(init_id), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 3).await;
+
+        let records = store.get_records_in_registry_order(0, 10).await.unwrap();
+        assert_eq!(
+            records,
+            vec![
+                (
+                    0,
+                    RecordKind::Operator(PublishedProtoEnvelope {
+                        envelope: operator,
+                        registry_index: 0
+                    })
+                ),
+                (
+                    1,
+                    RecordKind::Package(PublishedProtoEnvelope {
+                        envelope: init,
+                        registry_index: 1
+                    })
+                ),
+                (
+                    2,
+                    RecordKind::Operator(PublishedProtoEnvelope {
+                        envelope: define,
+                        registry_index: 2
+                    })
+                ),
+                (
+                    3,
+                    RecordKind::Package(PublishedProtoEnvelope {
+                        envelope: next,
+                        registry_index: 3
+                    })
+                ),
+            ]
+        );
+
+        let records = store.get_records_in_registry_order(1, 2).await.unwrap();
+        assert_eq!(
+            records.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert!(store
+            .get_records_in_registry_order(4, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fail_record_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let digest = content_digest("corrupt");
+        let (record_id, record) = package_init_record(&key, &[&digest]);
+        let (complete_id, complete) = package_init_record(&key, &[]);
+
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+        store
+            .store_package_record(&log_id, &name, &complete_id, &complete, &IndexSet::new())
+            .await
+            .unwrap();
+
+        store
+            .fail_record_content(&log_id, &record_id, "content is corrupt")
+            .await
+            .unwrap();
+
+        let rejected = store.get_package_record(&log_id, &record_id).await.unwrap();
+        assert!(matches!(
+            rejected.status,
+            crate::datastore::RecordStatus::Rejected(reason) if reason == "content is corrupt"
+        ));
+
+        // Records without missing content cannot have their content failed
+        assert!(matches!(
+            store
+                .fail_record_content(&log_id, &complete_id, "content is corrupt")
+                .await,
+            Err(DataStoreError::RecordNotPending(_))
+        ));
+        assert!(matches!(
+            store
+                .fail_record_content(&log_id, &record_id, "content is corrupt")
+                .await,
+            Err(DataStoreError::RecordNotPending(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_metadata() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let ts_checkpoint = checkpoint(&key, 1);
+        let metadata = IndexMap::from([("host".to_string(), "registry-1".to_string())]);
+
+        store
+            .store_checkpoint_with_metadata(
+                &content_digest("checkpoint"),
+                ts_checkpoint.clone(),
+                metadata.clone(),
+            )
+            .await
+            .unwrap();
+        store_checkpoint(&store, &key, 2).await;
+
+        assert_eq!(store.get_checkpoint_metadata(1).await.unwrap(), metadata);
+        assert!(store.get_checkpoint_metadata(2).await.unwrap().is_empty());
+        assert!(matches!(
+            store.get_checkpoint_metadata(3).await,
+            Err(DataStoreError::CheckpointNotFound(3))
+        ));
+
+        // The signed checkpoint is unchanged
+        assert_eq!(store.get_checkpoint(1).await.unwrap(), ts_checkpoint);
+    }
+
+    #[tokio::test]
+    async fn test_store_package_record_name_mismatch() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let other_name = PackageName::new("test:other").unwrap();
+
+        let (first_id, first) = package_init_record(&key, &[]);
+        store
+            .store_package_record(&log_id, &name, &first_id, &first, &IndexSet::new())
+            .await
+            .unwrap();
+
+        let (second_id, second) = package_record(&key, Some(first_id), vec![]);
+        match store
+            .store_package_record(&log_id, &other_name, &second_id, &second, &IndexSet::new())
+            .await
+        {
+            Err(DataStoreError::PackageNameMismatch { existing, got }) => {
+                assert_eq!(existing, name.to_string());
+                assert_eq!(got, other_name.to_string());
+            }
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert!(matches!(
+            store.get_package_record(&log_id, &second_id).await,
+            Err(DataStoreError::RecordNotFound(_))
+        ));
+
+        // The same name is accepted
+        store
+            .store_package_record(&log_id, &name, &second_id, &second, &IndexSet::new())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_store_record_already_exists() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        match store
+            .store_operator_record(&operator_log_id, &operator_id, &operator)
+            .await
+        {
+            Err(DataStoreError::RecordAlreadyExists(id)) => assert_eq!(id, operator_id),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert_eq!(
+            store
+                .get_operator_record(&operator_log_id, &operator_id)
+                .await
+                .unwrap()
+                .registry_index,
+            Some(0)
+        );
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+        match store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+        {
+            Err(DataStoreError::RecordAlreadyExists(id)) => assert_eq!(id, record_id),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert_eq!(
+            store
+                .get_package_record(&package.0, &record_id)
+                .await
+                .unwrap()
+                .registry_index,
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_recently_updated_logs() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let first = named_package("test:first");
+        let second = named_package("test:second");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let (second_id, second_record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &second, &second_id, &second_record, 1).await;
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let (first_id, first_record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &first, &first_id, &first_record, 2).await;
+
+        let logs = store.get_recently_updated_logs(10).await.unwrap();
+        assert_eq!(
+            logs.iter().map(|(log_id, _)| log_id).collect::<Vec<_>>(),
+            vec![&first.0, &second.0, &operator_log_id]
+        );
+        assert!(logs.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let logs = store.get_recently_updated_logs(1).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, first.0);
+    }
+
+    #[tokio::test]
+    async fn test_export_log() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        store_checkpoint(&store, &key, 1).await;
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        store_checkpoint(&store, &key, 3).await;
+        store_checkpoint(&store, &key, 4).await;
+
+        let bundle = store.export_log(log_id).await.unwrap();
+        assert_eq!(bundle.package_name.as_ref(), Some(name));
+        assert_eq!(
+            bundle
+                .records
+                .iter()
+                .map(|r| &r.record_id)
+                .collect::<Vec<_>>(),
+            vec![&init_id, &next_id]
+        );
+        assert_eq!(
+            bundle
+                .checkpoint
+                .as_ref()
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            3
+        );
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let imported: LogBundle = serde_json::from_str(&json).unwrap();
+        let state = imported.validate().unwrap();
+        assert_eq!(
+            state.head().as_ref().map(|head| &head.digest),
+            Some(&next_id)
+        );
+
+        // A bundle with a missing record fails validation
+        let mut tampered = imported;
+        tampered.records.remove(0);
+        match tampered.validate() {
+            Err(DataStoreError::RevalidationFailed { record_id, .. }) => {
+                assert_eq!(record_id, next_id)
+            }
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        // A log whose leaf is missing can't be exported
+        store.state.write().await.log_leafs.shift_remove(&2);
+        assert!(matches!(
+            store.export_log(log_id).await,
+            Err(DataStoreError::LogLeafNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_record_cache() {
+        let store = MemoryDataStore::new().with_record_cache(1);
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, _) = &package;
+        let cached = |store: &MemoryDataStore| {
+            store
+                .record_cache
+                .as_ref()
+                .unwrap()
+                .lock()
+                .unwrap()
+                .entries
+                .keys()
+                .map(|(_, record_id)| record_id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+
+        // Validated records are not cached, so publishing them is observed
+        let record = store.get_package_record(log_id, &init_id).await.unwrap();
+        assert_eq!(record.status, crate::datastore::RecordStatus::Validated);
+        assert!(cached(&store).is_empty());
+
+        store_checkpoint(&store, &key, 2).await;
+        for _ in 0..2 {
+            let record = store.get_package_record(log_id, &init_id).await.unwrap();
+            assert_eq!(record.status, crate::datastore::RecordStatus::Published);
+            assert_eq!(record.registry_index, Some(1));
+            assert_eq!(record.envelope.content_bytes(), init.content_bytes());
+        }
+        assert_eq!(cached(&store), vec![init_id.clone()]);
+
+        // Pending records are not cached
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store
+            .store_package_record(log_id, &package.1, &next_id, &next, &IndexSet::new())
+            .await
+            .unwrap();
+        let record = store.get_package_record(log_id, &next_id).await.unwrap();
+        assert_eq!(record.status, crate::datastore::RecordStatus::Pending);
+        assert_eq!(cached(&store), vec![init_id.clone()]);
+
+        // Caching another published record evicts the least recently used
+        store
+            .commit_package_record(log_id, &next_id, 2)
+            .await
+            .unwrap();
+        store_checkpoint(&store, &key, 3).await;
+        let record = store.get_package_record(log_id, &next_id).await.unwrap();
+        assert_eq!(record.envelope.content_bytes(), next.content_bytes());
+        assert_eq!(cached(&store), vec![next_id]);
+
+        let record = store.get_package_record(log_id, &init_id).await.unwrap();
+        assert_eq!(record.envelope.content_bytes(), init.content_bytes());
+        assert_eq!(cached(&store), vec![init_id]);
+    }
+
+    #[tokio::test]
+    async fn test_get_publishable_namespaces_for_key() {
+        let store = MemoryDataStore::new();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let (_, key) = generate_p256_pair();
+        let (publisher_key, publisher) = generate_p256_pair();
+        let publisher_id = publisher_key.fingerprint();
+
+        let (init_id, init) = operator_init_record(&key, &["first"]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+        let (grant_id, grant) = operator_record(
+            &key,
+            Some(init_id),
+            vec![
+                OperatorEntry::GrantFlat {
+                    key: publisher_key,
+                    permissions: vec![operator::Permission::DefineNamespace],
+                },
+                OperatorEntry::ImportNamespace {
+                    namespace: "imported".to_string(),
+                    registry: "example.com".to_string(),
+                },
+            ],
+        );
+        store_and_commit_operator(&store, &grant_id, &grant, 1).await;
+        let (define_id, define) = operator_record(
+            &publisher,
+            Some(grant_id),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "second".to_string(),
+            }],
+        );
+        store_and_commit_operator(&store, &define_id, &define, 2).await;
+
+        assert_eq!(
+            store
+                .get_publishable_namespaces_for_key(&operator_log_id, &publisher_id)
+                .await
+                .unwrap(),
+            vec!["second".to_string()]
+        );
+        assert_eq!(
+            store
+                .get_publishable_namespaces_for_key(
+                    &operator_log_id,
+                    &key.public_key().fingerprint()
+                )
+                .await
+                .unwrap(),
+            vec!["first".to_string()]
+        );
+
+        // A key without grants cannot publish anywhere
+        let (other, _) = generate_p256_pair();
+        assert!(store
+            .get_publishable_namespaces_for_key(&operator_log_id, &other.fingerprint())
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Revoking the grant revokes the namespaces the key defined
+        let (revoke_id, revoke) = operator_record(
+            &key,
+            Some(define_id),
+            vec![OperatorEntry::RevokeFlat {
+                key_id: publisher_id.clone(),
+                permissions: vec![operator::Permission::DefineNamespace],
+            }],
+        );
+        store_and_commit_operator(&store, &revoke_id, &revoke, 3).await;
+        assert!(store
+            .get_publishable_namespaces_for_key(&operator_log_id, &publisher_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_commit_emits_span() {
+        use std::fmt::Write;
+        use tracing::{
+            field::{Field, Visit},
+            span::{Attributes, Id},
+            Subscriber,
+        };
+        use tracing_subscriber::{
+            layer::{Context, SubscriberExt},
+            Layer,
+        };
+
+        #[derive(Clone, Default)]
+        struct SpanCapture(Arc<Mutex<Vec<(&'static str, String)>>>);
+
+        struct FieldVisitor(String);
+
+        impl Visit for FieldVisitor {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                write!(self.0, "{}={:?} ", field.name(), value).unwrap();
+            }
+        }
+
+        impl<S: Subscriber> Layer<S> for SpanCapture {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+                let mut visitor = FieldVisitor(String::new());
+                attrs.record(&mut visitor);
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((attrs.metadata().name(), visitor.0));
+            }
+        }
+
+        let capture = SpanCapture::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+        let (record_id, record) = operator_init_record(&key, &[]);
+        store_and_commit_operator(&store, &record_id, &record, 0).await;
+
+        let spans = capture.0.lock().unwrap();
+        let (_, fields) = spans
+            .iter()
+            .find(|(name, _)| *name == "commit_operator_record")
+            .expect("commit span should be emitted");
+        assert!(fields.contains(&format!("log_id={log_id}")));
+        assert!(fields.contains(&format!("record_id={record_id}")));
+        assert!(fields.contains("registry_index=0"));
+    }
+
+    #[tokio::test]
+    async fn test_get_inclusion_proof() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, _) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        store_checkpoint(&store, &key, 2).await;
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        store_checkpoint(&store, &key, 3).await;
+
+        let leafs = [
+            LogLeaf {
+                log_id: LogId::operator_log::<Sha256>(),
+                record_id: operator_id,
+            },
+            LogLeaf {
+                log_id: log_id.clone(),
+                record_id: init_id,
+            },
+            LogLeaf {
+                log_id: log_id.clone(),
+                record_id: next_id,
+            },
+        ];
+        let mut log = VecLog::<Sha256, LogLeaf>::default();
+        for leaf in &leafs {
+            log.push(leaf);
+        }
+        let root = log.checkpoint().root();
+
+        for (index, leaf) in leafs.iter().enumerate() {
+            let bundle = store
+                .get_inclusion_proof::<Sha256>(index, leafs.len())
+                .await
+                .unwrap();
+            let (data, _, proofs) = bundle.unbundle();
+            assert_eq!(proofs.len(), 1);
+            assert_eq!(proofs[0].evaluate_value(&data, leaf).unwrap(), root);
+        }
+
+        // Proofs against an earlier checkpoint evaluate to that checkpoint's root
+        let mut earlier = VecLog::<Sha256, LogLeaf>::default();
+        for leaf in &leafs[..2] {
+            earlier.push(leaf);
+        }
+        let bundle = store.get_inclusion_proof::<Sha256>(0, 2).await.unwrap();
+        let (data, _, proofs) = bundle.unbundle();
+        assert_eq!(
+            proofs[0].evaluate_value(&data, &leafs[0]).unwrap(),
+            earlier.checkpoint().root()
+        );
+
+        assert!(matches!(
+            store.get_inclusion_proof::<Sha256>(2, 2).await,
+            Err(DataStoreError::InclusionIndexOutOfRange {
+                index: 2,
+                length: 2
+            })
+        ));
+        assert!(matches!(
+            store.get_inclusion_proof::<Sha256>(0, 4).await,
+            Err(DataStoreError::CheckpointNotFound(4))
+        ));
+        // A length within the log that was never checkpointed is rejected too
+        assert!(matches!(
+            store.get_inclusion_proof::<Sha256>(0, 1).await,
+            Err(DataStoreError::CheckpointNotFound(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_consistency_proof() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, _) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        store_checkpoint(&store, &key, 2).await;
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        store_checkpoint(&store, &key, 3).await;
+
+        let mut log = VecLog::<Sha256, LogLeaf>::default();
+        let mut roots = Vec::new();
+        for (log_id, record_id) in [
+            (LogId::operator_log::<Sha256>(), operator_id),
+            (log_id.clone(), init_id),
+            (log_id.clone(), next_id),
+        ] {
+            log.push(&LogLeaf { log_id, record_id });
+            roots.push(log.checkpoint().root());
+        }
+
+        let bundle = store.get_consistency_proof::<Sha256>(2, 3).await.unwrap();
+        let (data, proofs, _) = bundle.unbundle();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(
+            proofs[0].evaluate(&data).unwrap(),
+            (roots[1].clone(), roots[2].clone())
+        );
+
+        assert!(matches!(
+            store.get_consistency_proof::<Sha256>(3, 2).await,
+            Err(DataStoreError::InvalidConsistencyRange { from: 3, to: 2 })
+        ));
+        assert!(matches!(
+            store.get_consistency_proof::<Sha256>(1, 3).await,
+            Err(DataStoreError::CheckpointNotFound(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_find_index_gaps() {
+        let store = MemoryDataStore::new();
+        assert!(store.find_index_gaps().await.unwrap().is_empty());
+
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        let (next_id, next) = package_record(&key, Some(init_id), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        assert!(store.find_index_gaps().await.unwrap().is_empty());
+
+        store.state.write().await.log_leafs.shift_remove(&1);
+        assert_eq!(store.find_index_gaps().await.unwrap(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_on_store() {
+        let store = MemoryDataStore::new().with_verify_on_store(true);
+        let (_, key) = generate_p256_pair();
+        let (_, other_key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        // The init entry claims `key`, but the envelope is signed by `other_key`
+        let (record_id, record) = package_record(
+            &other_key,
+            None,
+            vec![PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: key.public_key(),
+            }],
+        );
+        assert!(matches!(
+            store
+                .store_package_record(&log_id, &name, &record_id, &record, &IndexSet::new())
+                .await,
+            Err(DataStoreError::SignatureVerificationFailed(_))
+        ));
+        assert!(store.get_pending_records().await.unwrap().is_empty());
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(&log_id, &name, &record_id, &record, &IndexSet::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_pending_records().await.unwrap(),
+            vec![(log_id, record_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_governing_operator() {
+        let store = MemoryDataStore::new();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        assert_eq!(store.get_governing_operator(log_id).await.unwrap(), None);
+
+        // Verification alone leaves the store unchanged
+        let before = store.state.read().await.clone();
+        store
+            .verify_can_publish_package(&operator_log_id, name)
+            .await
+            .unwrap();
+        assert!(*store.state.read().await == before);
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+        assert_eq!(
+            store.get_governing_operator(log_id).await.unwrap(),
+            Some(operator_log_id.clone())
+        );
+
+        // The association is kept by log id, so it outlives the package name
+        store.unset_package_name(log_id).await.unwrap();
+        assert_eq!(
+            store.get_governing_operator(log_id).await.unwrap(),
+            Some(operator_log_id)
+        );
+
+        // A package whose namespace no operator log defines is not associated
+        let other = named_package("other:package");
+        let (other_id, other_record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &other.0,
+                &other.1,
+                &other_id,
+                &other_record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(store.get_governing_operator(&other.0).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_preview_commit_package_record() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        let preview = store
+            .preview_commit_package_record(log_id, &init)
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.get_package_log_state_hash(log_id).await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        assert_eq!(
+            store.get_package_log_state_hash(log_id).await.unwrap(),
+            preview
+        );
+
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        let preview = store
+            .preview_commit_package_record(log_id, &next)
+            .await
+            .unwrap();
+        let before = store.get_package_log_state_hash(log_id).await.unwrap();
+        assert_ne!(preview, before);
+        store
+            .store_package_record(log_id, name, &next_id, &next, &IndexSet::new())
+            .await
+            .unwrap();
+        store
+            .commit_package_record(log_id, &next_id, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_package_log_state_hash(log_id).await.unwrap(),
+            preview
+        );
+
+        // Invalid records report the validation error
+        let (_, invalid) = package_record(&key, Some(init_id), vec![]);
+        assert!(matches!(
+            store.preview_commit_package_record(log_id, &invalid).await,
+            Err(DataStoreError::PackageValidationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_publish_count() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let first = named_package("test:first");
+        let second = named_package("test:second");
+        let other = named_package("other:package");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test", "other"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (first_id, first_record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &first, &first_id, &first_record, 1).await;
+        let (next_id, next) = package_record(&key, Some(first_id), vec![]);
+        store_and_commit_package(&store, &first, &next_id, &next, 2).await;
+        let (second_id, second_record) = package_init_record(&key, &[&content_digest("second")]);
+        store_and_commit_package(&store, &second, &second_id, &second_record, 3).await;
+        let (other_id, other_record) = package_init_record(&key, &[&content_digest("other")]);
+        store_and_commit_package(&store, &other, &other_id, &other_record, 4).await;
+
+        let window = Duration::from_secs(60);
+        assert_eq!(store.get_publish_count("test", window).await.unwrap(), 3);
+        assert_eq!(store.get_publish_count("other", window).await.unwrap(), 1);
+        assert_eq!(store.get_publish_count("unknown", window).await.unwrap(), 0);
+
+        // Commits outside of the window are not counted
+        std::thread::sleep(Duration::from_millis(200));
+        let (last_id, last) = package_record(&key, Some(next_id), vec![]);
+        store_and_commit_package(&store, &first, &last_id, &last, 5).await;
+        assert_eq!(
+            store
+                .get_publish_count("test", Duration::from_millis(100))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(store.get_publish_count("test", window).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_get_checkpoint_lengths() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        assert!(store.get_checkpoint_lengths().await.unwrap().is_empty());
+
+        for log_length in [3, 1, 2] {
+            store_checkpoint(&store, &key, log_length).await;
+        }
+
+        assert_eq!(store.get_checkpoint_lengths().await.unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_commit_package_records_atomic() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, name) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        // The second record does not follow the first, so it is invalid
+        let (invalid_id, invalid) = package_record(&key, None, vec![]);
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        for (record_id, record) in [
+            (&init_id, &init),
+            (&invalid_id, &invalid),
+            (&next_id, &next),
+        ] {
+            store
+                .store_package_record(log_id, name, record_id, record, &IndexSet::new())
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(
+            store
+                .commit_package_records_atomic(
+                    log_id,
+                    &[
+                        (init_id.clone(), 1),
+                        (invalid_id.clone(), 2),
+                        (next_id.clone(), 3)
+                    ],
+                )
+                .await,
+            Err(DataStoreError::PackageValidationFailed(_))
+        ));
+        assert_eq!(store.current_registry_length().await.unwrap(), 1);
+        for record_id in [&init_id, &invalid_id, &next_id] {
+            let record = store.get_package_record(log_id, record_id).await.unwrap();
+            assert_eq!(record.status, crate::datastore::RecordStatus::Pending);
+        }
+
+        store
+            .commit_package_records_atomic(log_id, &[(init_id.clone(), 1), (next_id.clone(), 2)])
+            .await
+            .unwrap();
+        assert_eq!(store.current_registry_length().await.unwrap(), 3);
+        assert_eq!(
+            store.get_head_record_id(log_id).await.unwrap(),
+            Some(next_id)
+        );
+
+        // Committing an already committed record reports where it was committed
+        assert!(matches!(
+            store
+                .commit_package_records_atomic(log_id, &[(init_id.clone(), 5)])
+                .await,
+            Err(DataStoreError::RecordAlreadyCommitted { at: 1 })
+        ));
+        assert!(matches!(
+            store.commit_package_record(log_id, &init_id, 5).await,
+            Err(DataStoreError::RecordAlreadyCommitted { at: 1 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_signing_denial_reason() {
+        let store = MemoryDataStore::new();
+        let (_, alice) = generate_p256_pair();
+        let (_, bob) = generate_p256_pair();
+        let (_, carol) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (init_id, init) = operator_init_record(&alice, &[]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+        let (grant_id, grant) = operator_record(
+            &alice,
+            Some(init_id),
+            vec![OperatorEntry::GrantFlat {
+                key: bob.public_key(),
+                permissions: vec![operator::Permission::DefineNamespace],
+            }],
+        );
+        store_and_commit_operator(&store, &grant_id, &grant, 1).await;
+
+        assert_eq!(
+            store
+                .checkpoint_signing_denial_reason(&log_id, &alice.public_key().fingerprint())
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            store
+                .checkpoint_signing_denial_reason(&log_id, &bob.public_key().fingerprint())
+                .await
+                .unwrap(),
+            Some(CheckpointSigningDenial::MissingPermission(
+                operator::Permission::Commit
+            ))
+        );
+        assert_eq!(
+            store
+                .checkpoint_signing_denial_reason(&log_id, &carol.public_key().fingerprint())
+                .await
+                .unwrap(),
+            Some(CheckpointSigningDenial::UnknownKey)
+        );
+
+        // Verifying checkpoints distinguishes the two denials
+        for (key, unknown) in [(&bob, false), (&carol, true)] {
+            match store
+                .verify_timestamped_checkpoint_signature(&log_id, &checkpoint(key, 2))
+                .await
+            {
+                Err(DataStoreError::UnknownKey(_)) if unknown => {}
+                Err(DataStoreError::PermissionDenied { permission, .. }) if !unknown => {
+                    assert_eq!(permission, operator::Permission::Commit)
+                }
+                r => panic!("unexpected result: {r:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_package_records_reverse() {
+        use futures::TryStreamExt;
+
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, _) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        let mut prev = init_id;
+        for i in 0..3 {
+            let (id, record) = package_record(&key, Some(prev), vec![]);
+            store_and_commit_package(&store, &package, &id, &record, i + 2).await;
+            prev = id;
+        }
+        store_checkpoint(&store, &key, 4).await;
+        store_checkpoint(&store, &key, 5).await;
+
+        for registry_log_length in [4, 5] {
+            let mut forward = store
+                .get_package_records(log_id, registry_log_length, None, u16::MAX)
+                .await
+                .unwrap();
+            let reverse: Vec<_> = store
+                .stream_package_records_reverse(log_id, registry_log_length)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(reverse.len(), registry_log_length - 1);
+            forward.reverse();
+            assert_eq!(reverse, forward);
+        }
+
+        assert!(matches!(
+            store.stream_package_records_reverse(log_id, 3).await,
+            Err(DataStoreError::CheckpointNotFound(3))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_record_content_digests() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let first = content_digest("first");
+        let second = content_digest("second");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[&first, &second]);
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&second]),
+            )
+            .await
+            .unwrap();
+        store
+            .set_content_present(&log_id, &record_id, &second, Some(1))
+            .await
+            .unwrap();
+
+        let digests = store
+            .get_record_content_digests(&log_id, &record_id)
+            .await
+            .unwrap();
+        assert_eq!(digests, IndexSet::from([first, second]));
+
+        assert!(matches!(
+            store
+                .get_record_content_digests(&log_id, &content_digest("unknown").into())
+                .await,
+            Err(DataStoreError::RecordNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_checkpoint_signatures() {
+        let store = MemoryDataStore::new().with_record_ops(true);
+        let (_, key) = generate_p256_pair();
+        let (_, first) = generate_p256_pair();
+        let (_, second) = generate_p256_pair();
+
+        let primary = checkpoint(&key, 1);
+        let cosign = |key: &PrivateKey| {
+            SerdeEnvelope::signed_contents(key, primary.as_ref().clone()).unwrap()
+        };
+        let cosignatures = vec![cosign(&first), cosign(&second)];
+        store
+            .store_checkpoint_with_cosignatures(
+                &content_digest("checkpoint"),
+                primary.clone(),
+                cosignatures.clone(),
+            )
+            .await
+            .unwrap();
+
+        let signatures = store.get_checkpoint_signatures(1).await.unwrap();
+        assert_eq!(
+            signatures.iter().map(|s| s.key_id()).collect::<Vec<_>>(),
+            vec![
+                primary.key_id(),
+                cosignatures[0].key_id(),
+                cosignatures[1].key_id()
+            ]
+        );
+        assert_eq!(
+            store.get_latest_checkpoint().await.unwrap().key_id(),
+            primary.key_id()
+        );
+
+        // Co-signatures survive a replay of the recorded operations
+        let replayed = MemoryDataStore::replay_ops(&store.recorded_ops());
+        assert_eq!(
+            replayed.get_checkpoint_signatures(1).await.unwrap().len(),
+            3
+        );
+
+        // Co-signatures of a different checkpoint are rejected
+        assert!(matches!(
+            store
+                .store_checkpoint_with_cosignatures(
+                    &content_digest("other"),
+                    checkpoint(&key, 2),
+                    vec![cosign(&first)],
+                )
+                .await,
+            Err(DataStoreError::CosignatureMismatch { log_length: 2 })
+        ));
+        assert!(matches!(
+            store.get_checkpoint_signatures(2).await,
+            Err(DataStoreError::CheckpointNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_memory_bytes() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        assert_eq!(store.estimate_memory_bytes().await.unwrap(), 0);
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let before = store.estimate_memory_bytes().await.unwrap();
+        assert!(before >= operator.content_bytes().len() as u64);
+
+        let contents: Vec<_> = (0..32)
+            .map(|i| content_digest(&format!("content-{i}")))
+            .collect();
+        let (record_id, record) = package_init_record(&key, &contents.iter().collect::<Vec<_>>());
+        store
+            .store_package_record(&log_id, &name, &record_id, &record, &IndexSet::new())
+            .await
+            .unwrap();
+        let after = store.estimate_memory_bytes().await.unwrap();
+        assert!(after >= before + record.content_bytes().len() as u64);
+
+        // Committing the record does not count its contents twice
+        store
+            .commit_package_record(&log_id, &record_id, 1)
+            .await
+            .unwrap();
+        let committed = store.estimate_memory_bytes().await.unwrap();
+        assert!(committed < after + record.content_bytes().len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_replace_state() {
+        let store = MemoryDataStore::new().with_record_cache(8);
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let (log_id, _) = &package;
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        store_checkpoint(&store, &key, 2).await;
+        let snapshot = store.snapshot().await;
+
+        let (next_id, next) = package_record(&key, Some(init_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        store_checkpoint(&store, &key, 3).await;
+        // Cache the record that is not part of the snapshot
+        store.get_package_record(log_id, &next_id).await.unwrap();
+
+        store.replace_state(snapshot.clone()).await.unwrap();
+        assert!(*store.state.read().await == snapshot.state);
+        assert_eq!(store.current_registry_length().await.unwrap(), 2);
+        assert_eq!(
+            store.get_head_record_id(log_id).await.unwrap(),
+            Some(init_id)
+        );
+        assert!(matches!(
+            store.get_package_record(log_id, &next_id).await,
+            Err(DataStoreError::RecordNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_store_package_record_invalid_missing_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let content = content_digest("content");
+        let extraneous = content_digest("extraneous");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[&content]);
+        match store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&content, &extraneous]),
+            )
+            .await
+        {
+            Err(DataStoreError::InvalidMissingContent(digest)) => assert_eq!(digest, extraneous),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert!(store.get_pending_records().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_uncheckpointed_range() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        assert_eq!(store.get_uncheckpointed_range().await.unwrap(), None);
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        assert_eq!(
+            store.get_uncheckpointed_range().await.unwrap(),
+            Some((0, 1))
+        );
+
+        store_checkpoint(&store, &key, 1).await;
+        assert_eq!(store.get_uncheckpointed_range().await.unwrap(), None);
+
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+        let (next_id, next) = package_record(&key, Some(init_id), vec![]);
+        store_and_commit_package(&store, &package, &next_id, &next, 2).await;
+        assert_eq!(
+            store.get_uncheckpointed_range().await.unwrap(),
+            Some((1, 3))
+        );
+
+        store_checkpoint(&store, &key, 3).await;
+        assert_eq!(store.get_uncheckpointed_range().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_content_validation() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let content = content_digest("content");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        let (record_id, record) = package_init_record(&key, &[&content]);
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&content]),
+            )
+            .await
+            .unwrap();
+
+        // Missing content can neither be validated nor committed
+        assert_eq!(
+            store
+                .get_content_state(&log_id, &record_id, &content)
+                .await
+                .unwrap(),
+            ContentState::Missing
+        );
+        match store
+            .set_content_validated(&log_id, &record_id, &content)
+            .await
+        {
+            Err(DataStoreError::ContentNotPresent(digest)) => assert_eq!(digest, content),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        match store.commit_package_record(&log_id, &record_id, 1).await {
+            Err(DataStoreError::ContentNotPresent(digest)) => assert_eq!(digest, content),
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        // Present content still requires validation
+        assert!(store
+            .set_content_present(&log_id, &record_id, &content, Some(7))
+            .await
+            .unwrap());
+        assert_eq!(
+            store
+                .get_content_state(&log_id, &record_id, &content)
+                .await
+                .unwrap(),
+            ContentState::Present
+        );
+        match store.commit_package_record(&log_id, &record_id, 1).await {
+            Err(DataStoreError::ContentNotValidated(digest)) => assert_eq!(digest, content),
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        assert!(store
+            .set_content_validated(&log_id, &record_id, &content)
+            .await
+            .unwrap());
+        assert_eq!(
+            store
+                .get_content_state(&log_id, &record_id, &content)
+                .await
+                .unwrap(),
+            ContentState::Validated
+        );
+        store
+            .commit_package_record(&log_id, &record_id, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            store
+                .get_package_record(&log_id, &record_id)
+                .await
+                .unwrap()
+                .status,
+            crate::datastore::RecordStatus::Validated
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_records_committed_after() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let midpoint = SystemTime::now();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let (second_id, second) = package_record(&key, Some(first_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &second_id, &second, 2).await;
+        let (third_id, third) = package_record(&key, Some(second_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &third_id, &third, 3).await;
+
+        let leafs = store
+            .get_records_committed_after(midpoint, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            leafs,
+            [
+                (
+                    2,
+                    LogLeaf {
+                        log_id: package.0.clone(),
+                        record_id: second_id.clone(),
+                    }
+                ),
+                (
+                    3,
+                    LogLeaf {
+                        log_id: package.0.clone(),
+                        record_id: third_id,
+                    }
+                ),
+            ]
+        );
+
+        // The limit applies to the matching records
+        let leafs = store
+            .get_records_committed_after(midpoint, 1)
+            .await
+            .unwrap();
+        assert_eq!(leafs.len(), 1);
+        assert_eq!(leafs[0].0, 2);
+
+        assert_eq!(
+            store
+                .get_records_committed_after(SystemTime::UNIX_EPOCH, 10)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_status() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (log_id, name) = test_package();
+        let content = content_digest("content");
+        let operator_log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        assert_eq!(
+            store
+                .record_status(&operator_log_id, &operator_id)
+                .await
+                .unwrap(),
+            Some(crate::datastore::RecordStatus::Validated)
+        );
+
+        // Unknown records and logs
+        assert_eq!(
+            store.record_status(&log_id, &operator_id).await.unwrap(),
+            None
+        );
+        let (record_id, record) = package_init_record(&key, &[&content]);
+        assert_eq!(
+            store
+                .record_status(&operator_log_id, &record_id)
+                .await
+                .unwrap(),
+            None
+        );
+
+        store
+            .store_package_record(
+                &log_id,
+                &name,
+                &record_id,
+                &record,
+                &IndexSet::from([&content]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            store.record_status(&log_id, &record_id).await.unwrap(),
+            Some(crate::datastore::RecordStatus::MissingContent(vec![
+                content.clone()
+            ]))
+        );
+
+        store
+            .set_content_present(&log_id, &record_id, &content, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.record_status(&log_id, &record_id).await.unwrap(),
+            Some(crate::datastore::RecordStatus::Pending)
+        );
+
+        store
+            .reject_package_record(&log_id, &record_id, "bad record")
+            .await
+            .unwrap();
+        assert_eq!(
+            store.record_status(&log_id, &record_id).await.unwrap(),
+            Some(crate::datastore::RecordStatus::Rejected(
+                "bad record".to_string()
+            ))
+        );
+
+        store_checkpoint(&store, &key, 1).await;
+        assert_eq!(
+            store
+                .record_status(&operator_log_id, &operator_id)
+                .await
+                .unwrap(),
+            Some(crate::datastore::RecordStatus::Published)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_checkpoint_out_of_order() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let mut packages = Vec::new();
+        for i in 1..6 {
+            let package = named_package(&format!("test:package{i}"));
+            let (record_id, record) = package_init_record(&key, &[]);
+            store_and_commit_package(&store, &package, &record_id, &record, i).await;
+            packages.push((package, record_id));
+        }
+
+        // A shorter checkpoint stored later doesn't replace the latest
+        store_checkpoint(&store, &key, 5).await;
+        store_checkpoint(&store, &key, 3).await;
+
+        assert_eq!(
+            store
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            5
+        );
+        assert_eq!(
+            store.get_uncheckpointed_range().await.unwrap(),
+            Some((5, 6))
+        );
+
+        let ((log_id, _), record_id) = &packages[3];
+        assert_eq!(
+            store
+                .get_package_record(log_id, record_id)
+                .await
+                .unwrap()
+                .status,
+            crate::datastore::RecordStatus::Published
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_checkpoints_batch() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+
+        store
+            .store_checkpoints_batch(
+                (1..=3)
+                    .map(|len| {
+                        (
+                            content_digest(&format!("checkpoint-{len}")),
+                            checkpoint(&key, len),
+                        )
+                    })
+                    .collect(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(store.get_checkpoint_lengths().await.unwrap(), [1, 2, 3]);
+        assert_eq!(
+            store
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_checkpoints_batch_duplicate() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        store_checkpoint(&store, &key, 1).await;
+
+        match store
+            .store_checkpoints_batch(vec![
+                (content_digest("a"), checkpoint(&key, 2)),
+                (content_digest("b"), checkpoint(&key, 3)),
+                (content_digest("c"), checkpoint(&key, 2)),
+            ])
+            .await
+        {
+            Err(DataStoreError::DuplicateCheckpoint(2)) => {}
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        // No checkpoint of the batch was stored
+        assert_eq!(store.get_checkpoint_lengths().await.unwrap(), [1]);
+    }
+
+    #[tokio::test]
+    async fn test_get_operator_records_affecting_namespace() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (init_id, init) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+        let (other_id, other) = operator_record(
+            &key,
+            Some(init_id.clone()),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "other".to_string(),
+            }],
+        );
+        store_and_commit_operator(&store, &other_id, &other, 1).await;
+        let (import_id, import) = operator_record(
+            &key,
+            Some(other_id.clone()),
+            vec![
+                OperatorEntry::ImportNamespace {
+                    namespace: "imported".to_string(),
+                    registry: "example.com".to_string(),
+                },
+                OperatorEntry::DefineNamespace {
+                    namespace: "test-two".to_string(),
+                },
+            ],
+        );
+        store_and_commit_operator(&store, &import_id, &import, 2).await;
+
+        let records = store
+            .get_operator_records_affecting_namespace(&log_id, "test")
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].registry_index, 0);
+        assert_eq!(records[0].envelope, init);
+
+        let records = store
+            .get_operator_records_affecting_namespace(&log_id, "imported")
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].registry_index, 2);
+
+        assert!(store
+            .get_operator_records_affecting_namespace(&log_id, "unknown")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_package_alias() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let alias = PackageName::new("test:old-name").unwrap();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        // The target must be a package log
+        match store
+            .set_package_alias(alias.clone(), package.0.clone())
+            .await
+        {
+            Err(DataStoreError::LogNotFound(log_id)) => assert_eq!(log_id, package.0),
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+        assert_eq!(store.get_log_id_for_package(&alias).await.unwrap(), None);
+
+        store
+            .set_package_alias(alias.clone(), package.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_log_id_for_package(&alias).await.unwrap(),
+            Some(package.0.clone())
+        );
+        assert_eq!(
+            store.get_log_id_for_package(&package.1).await.unwrap(),
+            Some(package.0.clone())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_package_alias_conflict() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let other = named_package("test:other");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+        let (other_id, other_record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &other, &other_id, &other_record, 2).await;
+
+        match store
+            .set_package_alias(other.1.clone(), package.0.clone())
+            .await
+        {
+            Err(DataStoreError::AliasConflict(name)) => assert_eq!(name, other.1),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert_eq!(
+            store.get_log_id_for_package(&other.1).await.unwrap(),
+            Some(other.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_covering_checkpoint() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+
+        // Checkpoints are not necessarily stored in order
+        store_checkpoint(&store, &key, 5).await;
+        store_checkpoint(&store, &key, 2).await;
+
+        let covering = |index| {
+            let store = &store;
+            async move {
+                store
+                    .get_covering_checkpoint(index)
+                    .await
+                    .unwrap()
+                    .map(|c| c.as_ref().checkpoint.log_length)
+            }
+        };
+        assert_eq!(covering(0).await, Some(2));
+        assert_eq!(covering(1).await, Some(2));
+        assert_eq!(covering(2).await, Some(5));
+        assert_eq!(covering(4).await, Some(5));
+        assert_eq!(covering(5).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_record_content_bytes() {
+        use warg_crypto::{Decode, Signable};
+
+        let store = MemoryDataStore::new();
+        let (public_key, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let bytes = store
+            .get_record_content_bytes(&LogId::operator_log::<Sha256>(), &operator_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            operator::OperatorRecord::decode(&bytes).unwrap(),
+            *operator.as_ref()
+        );
+
+        // Pending and rejected records return their bytes too
+        let (record_id, record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        let pending = store
+            .get_record_content_bytes(&package.0, &record_id)
+            .await
+            .unwrap();
+        store
+            .reject_package_record(&package.0, &record_id, "rejected")
+            .await
+            .unwrap();
+        let rejected = store
+            .get_record_content_bytes(&package.0, &record_id)
+            .await
+            .unwrap();
+        assert_eq!(pending, rejected);
+
+        // The bytes round-trip into the signed record
+        assert_eq!(
+            package::PackageRecord::decode(&rejected).unwrap(),
+            *record.as_ref()
+        );
+        package::PackageRecord::verify(&public_key, &rejected, record.signature()).unwrap();
+
+        match store
+            .get_record_content_bytes(&package.0, &operator_id)
+            .await
+        {
+            Err(DataStoreError::RecordNotFound(id)) => assert_eq!(id, operator_id),
+            r => panic!("unexpected result: {r:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_awaiting_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let first = named_package("test:first");
+        let second = named_package("test:second");
+        let complete = named_package("test:complete");
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        assert!(store.get_logs_awaiting_content().await.unwrap().is_empty());
+
+        for (package, name) in [(&second, "b"), (&complete, "c"), (&first, "a")] {
+            let digest = content_digest(name);
+            let (record_id, record) = package_init_record(&key, &[&digest]);
+            let missing = if package == &complete {
+                IndexSet::new()
+            } else {
+                IndexSet::from([&digest])
+            };
+            store
+                .store_package_record(&package.0, &package.1, &record_id, &record, &missing)
+                .await
+                .unwrap();
+        }
+
+        // A second record awaiting content in the same log is not duplicated
+        let digest = content_digest("a2");
+        let (record_id, record) = package_init_record(&key, &[&digest]);
+        store
+            .store_package_record(
+                &first.0,
+                &first.1,
+                &record_id,
+                &record,
+                &IndexSet::from([&digest]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get_logs_awaiting_content().await.unwrap(),
+            [second.0.clone(), first.0.clone()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_current_root() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let root_of = |len| {
+            let store = &store;
+            async move {
+                let state = store.state.read().await;
+                let root: AnyHash = state
+                    .verifiable_log::<Sha256>(len)
+                    .0
+                    .checkpoint()
+                    .root()
+                    .into();
+                root
+            }
+        };
+        assert_eq!(store.get_current_root().await.unwrap(), root_of(0).await);
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (mut prev_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &prev_id, &record, 1).await;
+        assert_eq!(store.get_current_root().await.unwrap(), root_of(2).await);
+
+        for index in 2..7 {
+            let (record_id, record) = package_record(&key, Some(prev_id), vec![]);
+            store_and_commit_package(&store, &package, &record_id, &record, index).await;
+            assert_eq!(
+                store.get_current_root().await.unwrap(),
+                root_of(index + 1).await
+            );
+            prev_id = record_id;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_operator_records_by_entry_kind() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let (other_key, _) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (init_id, init) = operator_init_record(&key, &[]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+        let (grant_id, grant) = operator_record(
+            &key,
+            Some(init_id.clone()),
+            vec![OperatorEntry::GrantFlat {
+                key: other_key,
+                permissions: vec![operator::Permission::Commit],
+            }],
+        );
+        store_and_commit_operator(&store, &grant_id, &grant, 1).await;
+        let (define_id, define) = operator_record(
+            &key,
+            Some(grant_id.clone()),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "test".to_string(),
+            }],
+        );
+        store_and_commit_operator(&store, &define_id, &define, 2).await;
+
+        let records = store
+            .get_operator_records_by_entry_kind(&log_id, OperatorEntryKind::DefineNamespace)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].registry_index, 2);
+        assert_eq!(records[0].envelope, define);
+
+        let records = store
+            .get_operator_records_by_entry_kind(&log_id, OperatorEntryKind::Init)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].envelope, init);
+
+        assert!(store
+            .get_operator_records_by_entry_kind(&log_id, OperatorEntryKind::ImportNamespace)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_package_name() {
+        let store = MemoryDataStore::new();
+        let (owner, owner_key) = generate_p256_pair();
+        let (_, other_key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&owner_key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+
+        store
+            .reserve_package_name(package.1.clone(), owner.fingerprint())
+            .await
+            .unwrap();
+
+        let (record_id, record) = package_init_record(&owner_key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+
+        // Names without a reservation are free to claim
+        let unreserved = named_package("test:unreserved");
+        let (record_id, record) = package_init_record(&other_key, &[]);
+        store
+            .store_package_record(
+                &unreserved.0,
+                &unreserved.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reserve_package_name_conflict() {
+        let store = MemoryDataStore::new();
+        let (owner, _) = generate_p256_pair();
+        let (other, other_key) = generate_p256_pair();
+        let package = test_package();
+
+        store
+            .reserve_package_name(package.1.clone(), owner.fingerprint())
+            .await
+            .unwrap();
+        match store
+            .reserve_package_name(package.1.clone(), other.fingerprint())
+            .await
+        {
+            Err(DataStoreError::PackageNameReserved(name)) => assert_eq!(name, package.1),
+            r => panic!("unexpected result: {r:?}"),
+        }
+
+        let (record_id, record) = package_init_record(&other_key, &[]);
+        match store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+        {
+            Err(DataStoreError::PackageNameReserved(name)) => assert_eq!(name, package.1),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert!(store.get_pending_records().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_validated_records_chunked() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (mut prev_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &prev_id, &record, 1).await;
+        for index in 2..7 {
+            let (record_id, record) = package_record(&key, Some(prev_id), vec![]);
+            store_and_commit_package(&store, &package, &record_id, &record, index).await;
+            prev_id = record_id;
+        }
+
+        let expected = store
+            .get_all_validated_records_from(0)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(expected.len(), 7);
+
+        for chunk_size in [0, 1, 3, 7, 100] {
+            let leafs = store
+                .stream_validated_records_chunked(chunk_size)
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(leafs, expected, "chunk size {chunk_size}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_records_by_id_prefix() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (first_id, first) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &first_id, &first, 1).await;
+        let (second_id, second) = package_record(&key, Some(first_id.clone()), vec![]);
+        store_and_commit_package(&store, &package, &second_id, &second, 2).await;
+
+        // An ambiguous prefix
+        assert_eq!(
+            store
+                .find_records_by_id_prefix(&package.0, "sha256:")
+                .await
+                .unwrap(),
+            [first_id.clone(), second_id]
+        );
+
+        // A unique prefix
+        let id = first_id.to_string();
+        assert_eq!(
+            store
+                .find_records_by_id_prefix(&package.0, &id[..id.len() - 4])
+                .await
+                .unwrap(),
+            [first_id]
+        );
+
+        // Digests are hex encoded, so no record matches
+        assert!(store
+            .find_records_by_id_prefix(&package.0, "sha256:z")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_signing_keys() {
+        let store = MemoryDataStore::new();
+        let (first, first_key) = generate_p256_pair();
+        let (second, second_key) = generate_p256_pair();
+        let log_id = LogId::operator_log::<Sha256>();
+
+        let (init_id, init) = operator_init_record(&first_key, &["test"]);
+        store_and_commit_operator(&store, &init_id, &init, 0).await;
+        let (grant_id, grant) = operator_record(
+            &first_key,
+            Some(init_id),
+            vec![OperatorEntry::GrantFlat {
+                key: second.clone(),
+                permissions: vec![operator::Permission::DefineNamespace],
+            }],
+        );
+        store_and_commit_operator(&store, &grant_id, &grant, 1).await;
+        let (define_id, define) = operator_record(
+            &second_key,
+            Some(grant_id.clone()),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "other".to_string(),
+            }],
+        );
+        store_and_commit_operator(&store, &define_id, &define, 2).await;
+
+        assert_eq!(
+            store
+                .get_signing_keys(&log_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [first.fingerprint(), second.fingerprint()]
+        );
+
+        let package = test_package();
+        assert!(matches!(
+            store.get_signing_keys(&package.0).await,
+            Err(DataStoreError::LogNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_max_page_limit() {
+        let store = MemoryDataStore::new().with_max_page_limit(2);
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (mut prev_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &prev_id, &record, 1).await;
+        for index in 2..5 {
+            let (record_id, record) = package_record(&key, Some(prev_id), vec![]);
+            store_and_commit_package(&store, &package, &record_id, &record, index).await;
+            prev_id = record_id;
+        }
+        store_checkpoint(&store, &key, 5).await;
+
+        let records = store
+            .get_package_records(&package.0, 5, None, u16::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.registry_index).collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        // Limits below the cap are unaffected
+        assert_eq!(
+            store
+                .get_package_records(&package.0, 5, None, 1)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_operator_records(&operator_log_id, 5, None, 100)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Paging continues past the clamped page
+        let (page, cursor) = store
+            .get_package_records_paged(&package.0, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 2);
+        let (page, cursor) = store
+            .get_package_records_paged(&package.0, cursor, 100)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 2);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn test_verify_log_id() {
+        let store = MemoryDataStore::new();
+        let (log_id, name) = test_package();
+        let (other_log_id, other_name) = named_package("test:other");
+
+        assert!(store.verify_log_id(&log_id, &name).unwrap());
+        assert!(store.verify_log_id(&other_log_id, &other_name).unwrap());
+        assert!(!store.verify_log_id(&log_id, &other_name).unwrap());
+        assert!(!store
+            .verify_log_id(&LogId::operator_log::<Sha256>(), &name)
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_record_supports_content() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+        let operator_log_id = LogId::operator_log::<Sha256>();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store
+            .store_operator_record(&operator_log_id, &operator_id, &operator)
+            .await
+            .unwrap();
+        assert!(!store
+            .record_supports_content(&operator_log_id, &operator_id)
+            .await
+            .unwrap());
+        store
+            .commit_operator_record(&operator_log_id, &operator_id, 0)
+            .await
+            .unwrap();
+        assert!(!store
+            .record_supports_content(&operator_log_id, &operator_id)
+            .await
+            .unwrap());
+
+        let (record_id, record) = package_init_record(&key, &[]);
+        store
+            .store_package_record(
+                &package.0,
+                &package.1,
+                &record_id,
+                &record,
+                &IndexSet::new(),
+            )
+            .await
+            .unwrap();
+        assert!(store
+            .record_supports_content(&package.0, &record_id)
+            .await
+            .unwrap());
+        store
+            .commit_package_record(&package.0, &record_id, 1)
+            .await
+            .unwrap();
+        assert!(store
+            .record_supports_content(&package.0, &record_id)
+            .await
+            .unwrap());
+
+        match store
+            .record_supports_content(&package.0, &operator_id)
+            .await
+        {
+            Err(DataStoreError::RecordNotFound(id)) => assert_eq!(id, operator_id),
+            r => panic!("unexpected result: {r:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_package_entries_by_range() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (mut prev_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &prev_id, &record, 1).await;
+        for index in 2..5 {
+            let (record_id, record) = package_record(&key, Some(prev_id), vec![]);
+            store_and_commit_package(&store, &package, &record_id, &record, index).await;
+            prev_id = record_id;
+        }
+
+        let indexes = |records: Vec<PublishedProtoEnvelope<package::PackageRecord>>| {
+            records.iter().map(|r| r.registry_index).collect::<Vec<_>>()
+        };
+
+        let records = store
+            .get_package_entries_by_range(&package.0, 1, 3)
+            .await
+            .unwrap();
+        assert_eq!(indexes(records), [2, 3]);
+
+        // The end of the range is clamped to the log's length
+        let records = store
+            .get_package_entries_by_range(&package.0, 2, 100)
+            .await
+            .unwrap();
+        assert_eq!(indexes(records), [3, 4]);
+        assert!(store
+            .get_package_entries_by_range(&package.0, 4, 100)
+            .await
+            .unwrap()
+            .is_empty());
+
+        match store.get_package_entries_by_range(&package.0, 5, 6).await {
+            Err(DataStoreError::EntryRangeOutOfBounds { from, length }) => {
+                assert_eq!((from, length), (5, 4));
+            }
+            r => panic!("unexpected result: {r:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_self_referential_record() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (init_id, init) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &init_id, &init, 1).await;
+
+        // A genuine record id hashes over `prev`, so a record cannot name
+        // itself; but ids are not checked when stored, so a record can be
+        // stored under a fabricated id that matches its previous record
+        let self_id: RecordId = content_digest("self").into();
+        let (_, record) = package_record(&key, Some(self_id.clone()), vec![]);
+        store
+            .store_package_record(&package.0, &package.1, &self_id, &record, &IndexSet::new())
+            .await
+            .unwrap();
+
+        match store.commit_package_record(&package.0, &self_id, 2).await {
+            Err(DataStoreError::SelfReferentialRecord(id)) => assert_eq!(id, self_id),
+            r => panic!("unexpected result: {r:?}"),
+        }
+        assert!(matches!(
+            store
+                .get_package_record(&package.0, &self_id)
+                .await
+                .unwrap()
+                .status,
+            crate::datastore::RecordStatus::Rejected(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_handle() {
+        let store = MemoryDataStore::new();
+        let (_, key) = generate_p256_pair();
+        let package = test_package();
+
+        let handle = store.read_handle();
+        let cloned = handle.clone();
+        assert_eq!(handle.current_registry_length().await.unwrap(), 0);
+
+        let (operator_id, operator) = operator_init_record(&key, &["test"]);
+        store_and_commit_operator(&store, &operator_id, &operator, 0).await;
+        let (record_id, record) = package_init_record(&key, &[]);
+        store_and_commit_package(&store, &package, &record_id, &record, 1).await;
+        store_checkpoint(&store, &key, 2).await;
+
+        // Handles created before the writes see them
+        for handle in [&handle, &cloned] {
+            assert_eq!(handle.current_registry_length().await.unwrap(), 2);
+            let found = handle
+                .get_package_record(&package.0, &record_id)
+                .await
+                .unwrap();
+            assert_eq!(found.status, crate::datastore::RecordStatus::Published);
+            assert_eq!(found.envelope, record);
+        }
+
+        // The read methods of the store are available through handles
+        assert_eq!(
+            handle.get_current_root().await.unwrap(),
+            store.get_current_root().await.unwrap()
+        );
+        assert_eq!(handle.get_checkpoint_lengths().await.unwrap(), vec![2]);
+
+        // Closing the store is seen by its handles, which can still read
+        assert!(!handle.is_closed());
+        store.close();
+        assert!(handle.is_closed() && cloned.is_closed());
+        assert_eq!(handle.current_registry_length().await.unwrap(), 2);
+
+        // Handles can be used where a read-only store is expected
+        let read_only: &dyn crate::datastore::ReadOnlyDataStore = &handle;
+        assert_eq!(
+            read_only
+                .get_latest_checkpoint()
+                .await
+                .unwrap()
+                .as_ref()
+                .checkpoint
+                .log_length,
+            2
+        );
     }
 }
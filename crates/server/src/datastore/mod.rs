@@ -1,10 +1,11 @@
 use futures::Stream;
 use indexmap::{IndexMap, IndexSet};
-use std::pin::Pin;
+use std::{fmt, pin::Pin};
 use thiserror::Error;
 use warg_crypto::{
-    hash::AnyHash,
+    hash::{AnyHash, Hash, Sha256},
     signing::{KeyID, Signature},
+    Encode, Signable,
 };
 use warg_protocol::{
     operator, package,
@@ -30,6 +31,9 @@ pub enum DataStoreError {
     #[error("checkpoint log length `{0}` was not found")]
     CheckpointNotFound(RegistryLen),
 
+    #[error("no checkpoint has been stored")]
+    NoCheckpoint,
+
     #[error("log `{0}` was not found")]
     LogNotFound(LogId),
 
@@ -39,23 +43,103 @@ pub enum DataStoreError {
     #[error("log leaf {0} was not found")]
     LogLeafNotFound(RegistryIndex),
 
+    #[error("registry index {index} is not included in a log of length {length}")]
+    InclusionIndexOutOfRange {
+        index: RegistryIndex,
+        length: RegistryLen,
+    },
+
+    #[error("cannot prove consistency from log length {from} to the shorter log length {to}")]
+    InvalidConsistencyRange { from: RegistryLen, to: RegistryLen },
+
+    #[error("entry index {from} is past the end of a log with {length} entries")]
+    EntryRangeOutOfBounds { from: usize, length: usize },
+
+    #[error("failed to bundle proofs: {0}")]
+    ProofBundleFailed(String),
+
     #[error("record `{0}` cannot be validated as it is not in a pending state")]
     RecordNotPending(RecordId),
 
+    #[error("the record has already been committed at registry index {at}")]
+    RecordAlreadyCommitted { at: RegistryIndex },
+
+    #[error("record `{0}` has already been stored")]
+    RecordAlreadyExists(RecordId),
+
+    #[error("registry index {got} cannot be committed: the next registry index is {expected}")]
+    NonContiguousRegistryIndex {
+        expected: RegistryIndex,
+        got: RegistryIndex,
+    },
+
+    #[error("record `{0}` declares itself as its previous record")]
+    SelfReferentialRecord(RecordId),
+
     #[error("contents for record `{record_id}` are invalid: {message}")]
     InvalidRecordContents {
         record_id: RecordId,
         message: String,
     },
 
+    #[error("missing content `{0}` is not part of the record's contents")]
+    InvalidMissingContent(AnyHash),
+
+    #[error("more than {limit} records match the id prefix `{prefix}`")]
+    TooManyMatches { prefix: String, limit: usize },
+
+    #[error("package name `{0}` is reserved for another key")]
+    PackageNameReserved(PackageName),
+
+    #[error("alias `{0}` conflicts with the name of an existing package")]
+    AliasConflict(PackageName),
+
+    #[error("multiple checkpoints have log length {0}")]
+    DuplicateCheckpoint(RegistryLen),
+
+    #[error("content `{0}` has not been marked as present")]
+    ContentNotPresent(AnyHash),
+
+    #[error("content `{0}` has not been validated")]
+    ContentNotValidated(AnyHash),
+
+    #[error("content `{digest}` was previously recorded with size {expected} but was presented with size {got}")]
+    ContentSizeMismatch {
+        digest: AnyHash,
+        expected: u64,
+        got: u64,
+    },
+
+    #[error("the record is {size} bytes, which exceeds the maximum of {max} bytes")]
+    RecordTooLarge { size: usize, max: usize },
+
     #[error("the operator record was invalid: {0}")]
     OperatorValidationFailed(#[from] operator::ValidationError),
 
     #[error("the package record was invalid: {0}")]
     PackageValidationFailed(#[from] package::ValidationError),
 
-    #[error("the package namespace `{0}` is not defined")]
-    PackageNamespaceNotDefined(String),
+    #[error("record `{record_id}` failed revalidation: {source}")]
+    RevalidationFailed {
+        record_id: RecordId,
+        #[source]
+        source: package::ValidationError,
+    },
+
+    #[error("the revalidated state of log `{0}` does not match the stored state")]
+    LogStateMismatch(LogId),
+
+    #[error(
+        "the log is already recorded as package `{existing}` but the record is for package `{got}`"
+    )]
+    PackageNameMismatch { existing: String, got: String },
+
+    #[error("the package namespace `{namespace}` is not defined{}", format_suggestions(.suggestions))]
+    PackageNamespaceNotDefined {
+        namespace: String,
+        /// The defined namespaces nearest to the requested one.
+        suggestions: Vec<String>,
+    },
 
     #[error(
         "the package namespace `{0}` is imported from another registry and cannot accept publishes"
@@ -65,15 +149,36 @@ pub enum DataStoreError {
     #[error("key id `{0}` does not have permission")]
     KeyUnauthorized(KeyID),
 
+    #[error("key id `{key_id}` does not have the `{permission}` permission")]
+    PermissionDenied {
+        key_id: KeyID,
+        permission: operator::Permission,
+    },
+
     #[error("unknown key id `{0}`")]
     UnknownKey(KeyID),
 
     #[error("signature `{0}` verification failed")]
     SignatureVerificationFailed(Signature),
 
+    #[error("a co-signature does not sign the checkpoint of log length {log_length}")]
+    CosignatureMismatch { log_length: RegistryLen },
+
+    #[error("signature verification failed for checkpoint at index {index}")]
+    CheckpointSignatureFailed { index: usize },
+
     #[error("the record was rejected: {0}")]
     Rejection(String),
 
+    #[error("the data store has been closed")]
+    StoreClosed,
+
+    #[error("the page cursor is invalid")]
+    InvalidCursor,
+
+    #[error("record `{0}` is not a validated record of the log")]
+    InvalidSinceRecord(RecordId),
+
     #[cfg(feature = "postgres")]
     #[error("a connection could not be established to the PostgreSQL server: {0}")]
     ConnectionPool(#[from] diesel_async::pooled_connection::deadpool::PoolError),
@@ -83,6 +188,260 @@ pub enum DataStoreError {
     Diesel(#[from] diesel::result::Error),
 }
 
+fn format_suggestions(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        " (did you mean {}?)",
+        suggestions
+            .iter()
+            .map(|s| format!("`{s}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// The maximum number of namespace suggestions included in errors.
+const MAX_NAMESPACE_SUGGESTIONS: usize = 3;
+
+/// Ensures the set of missing content is a subset of the record's contents.
+///
+/// Missing content outside of the record could never be provided, leaving the
+/// record pending forever.
+fn ensure_missing_in_contents(
+    record: &ProtoEnvelope<package::PackageRecord>,
+    missing: &IndexSet<&AnyHash>,
+) -> Result<(), DataStoreError> {
+    use warg_protocol::Record;
+
+    let contents = record.as_ref().contents();
+    match missing.iter().find(|digest| !contents.contains(**digest)) {
+        Some(digest) => Err(DataStoreError::InvalidMissingContent((*digest).clone())),
+        None => Ok(()),
+    }
+}
+
+/// Creates the error for a namespace that is not defined by the operator,
+/// suggesting the nearest defined namespaces.
+fn namespace_not_defined(state: &operator::LogState, namespace: &str) -> DataStoreError {
+    let mut defined = state
+        .namespaces()
+        .filter(|(_, state)| matches!(state, operator::NamespaceState::Defined))
+        .map(|(defined, _)| (edit_distance(namespace, defined), defined))
+        .collect::<Vec<_>>();
+    defined.sort();
+
+    DataStoreError::PackageNamespaceNotDefined {
+        namespace: namespace.to_string(),
+        suggestions: defined
+            .into_iter()
+            .take(MAX_NAMESPACE_SUGGESTIONS)
+            .map(|(_, defined)| defined.to_string())
+            .collect(),
+    }
+}
+
+/// Computes the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+/// The reason a key is denied signing checkpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointSigningDenial {
+    /// The key is not known to the operator log.
+    UnknownKey,
+    /// The key is known to the operator log but lacks a permission.
+    MissingPermission(operator::Permission),
+}
+
+/// Determines why the given key may not sign checkpoints.
+///
+/// Returns `None` if the key is authorized to sign checkpoints.
+fn checkpoint_signing_denial(
+    state: &operator::LogState,
+    key_id: &KeyID,
+) -> Option<CheckpointSigningDenial> {
+    if state.public_key(key_id).is_none() {
+        Some(CheckpointSigningDenial::UnknownKey)
+    } else if !state.key_has_permission_to_sign_checkpoints(key_id) {
+        Some(CheckpointSigningDenial::MissingPermission(
+            operator::Permission::Commit,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Verifies a TimestampedCheckpoint signature against the given operator state.
+fn verify_checkpoint_signature(
+    state: &operator::LogState,
+    ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+) -> Result<(), DataStoreError> {
+    TimestampedCheckpoint::verify(
+        state
+            .public_key(ts_checkpoint.key_id())
+            .ok_or(DataStoreError::UnknownKey(ts_checkpoint.key_id().clone()))?,
+        &ts_checkpoint.as_ref().encode(),
+        ts_checkpoint.signature(),
+    )
+    .or(Err(DataStoreError::SignatureVerificationFailed(
+        ts_checkpoint.signature().clone(),
+    )))?;
+
+    if let Some(CheckpointSigningDenial::MissingPermission(permission)) =
+        checkpoint_signing_denial(state, ts_checkpoint.key_id())
+    {
+        return Err(DataStoreError::PermissionDenied {
+            key_id: ts_checkpoint.key_id().clone(),
+            permission,
+        });
+    }
+
+    Ok(())
+}
+
+/// Verifies TimestampedCheckpoint signatures against the given operator state.
+fn verify_checkpoint_signatures(
+    state: &operator::LogState,
+    checkpoints: &[SerdeEnvelope<TimestampedCheckpoint>],
+) -> Result<(), DataStoreError> {
+    for (index, ts_checkpoint) in checkpoints.iter().enumerate() {
+        verify_checkpoint_signature(state, ts_checkpoint)
+            .map_err(|_| DataStoreError::CheckpointSignatureFailed { index })?;
+    }
+
+    Ok(())
+}
+
+/// An opaque cursor for paging through records.
+///
+/// Cursors are returned by paged queries and passed back to fetch the next
+/// page; their contents are not meant to be interpreted by clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageCursor(String);
+
+impl PageCursor {
+    fn new(registry_index: RegistryIndex) -> Self {
+        Self(format!(
+            "{registry_index:x}.{checksum:08x}",
+            checksum = Self::checksum(registry_index)
+        ))
+    }
+
+    /// Gets the registry index the cursor refers to.
+    fn registry_index(&self) -> Result<RegistryIndex, DataStoreError> {
+        let (index, checksum) = self
+            .0
+            .split_once('.')
+            .ok_or(DataStoreError::InvalidCursor)?;
+        let index =
+            RegistryIndex::from_str_radix(index, 16).map_err(|_| DataStoreError::InvalidCursor)?;
+        let checksum =
+            u32::from_str_radix(checksum, 16).map_err(|_| DataStoreError::InvalidCursor)?;
+        if checksum != Self::checksum(index) {
+            return Err(DataStoreError::InvalidCursor);
+        }
+
+        Ok(index)
+    }
+
+    fn checksum(registry_index: RegistryIndex) -> u32 {
+        let hash = Hash::<Sha256>::of((registry_index as u64).to_be_bytes().as_slice());
+        u32::from_be_bytes(hash.bytes()[..4].try_into().unwrap())
+    }
+
+    /// Gets the cursor as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for PageCursor {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl fmt::Display for PageCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Determines how a `since` record that is not a validated record of the log
+/// is handled when fetching records.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum SinceBehavior {
+    /// Records are returned from the start of the log.
+    #[default]
+    Lenient,
+    /// An `InvalidSinceRecord` error is returned.
+    Error,
+}
+
+/// Represents the kind of log a record belongs to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LogKind {
+    /// The operator log.
+    Operator,
+    /// A package log.
+    Package,
+}
+
+/// Totals describing the contents of a data store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreStats {
+    /// The number of operator logs.
+    pub operator_logs: u64,
+    /// The number of package logs.
+    pub package_logs: u64,
+    /// The number of validated records.
+    pub validated_records: u64,
+    /// The number of pending records.
+    pub pending_records: u64,
+    /// The number of rejected records.
+    pub rejected_records: u64,
+    /// The number of checkpoints.
+    pub checkpoints: u64,
+}
+
+/// Hooks notified of record operations performed by a data store.
+///
+/// This can be used to export metrics such as the number of records
+/// stored, committed, and rejected.
+///
+/// All methods default to doing nothing.
+pub trait DataStoreMetrics: Send + Sync {
+    /// Called when a record is stored as pending.
+    fn on_store(&self, _kind: LogKind) {}
+
+    /// Called when a record is committed to its log.
+    fn on_commit(&self, _kind: LogKind) {}
+
+    /// Called when a record is rejected, either explicitly or
+    /// because it failed validation on commit.
+    fn on_reject(&self, _kind: LogKind) {}
+}
+
+/// Data store metrics hooks that do nothing.
+pub struct NoopDataStoreMetrics;
+
+impl DataStoreMetrics for NoopDataStoreMetrics {}
+
 /// Represents the status of a record.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum RecordStatus {
@@ -99,6 +458,7 @@ pub enum RecordStatus {
 }
 
 /// Represents a record in a log.
+#[derive(Clone)]
 pub struct Record<T>
 where
     T: Clone,
@@ -114,31 +474,11 @@ where
 }
 
 /// Implemented by data stores.
+///
+/// The methods that only read from the store are on its supertrait,
+/// [`ReadOnlyDataStore`].
 #[axum::async_trait]
-pub trait DataStore: Send + Sync {
-    /// Gets a stream of all checkpoints.
-    ///
-    /// This is an expensive operation and should only be performed on startup.
-    async fn get_all_checkpoints(
-        &self,
-    ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
-        DataStoreError,
-    >;
-
-    /// Gets a stream of all validated records.
-    ///
-    /// This is an expensive operation and should only be performed on startup.
-    async fn get_all_validated_records(
-        &self,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>;
-
-    /// Looks up the log_id and record_id from the registry log index.  
-    async fn get_log_leafs_with_registry_index(
-        &self,
-        entries: &[RegistryIndex],
-    ) -> Result<Vec<LogLeaf>, DataStoreError>;
-
+pub trait DataStore: ReadOnlyDataStore {
     /// Stores the given operator record.
     async fn store_operator_record(
         &self,
@@ -169,6 +509,20 @@ pub trait DataStore: Send + Sync {
         registry_index: RegistryIndex,
     ) -> Result<(), DataStoreError>;
 
+    /// Stores the given operator record and immediately commits it.
+    ///
+    /// Operator records have no content, so they can be committed as soon as
+    /// they are stored.
+    ///
+    /// If validation fails, the record will be marked as rejected.
+    async fn store_and_commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError>;
+
     /// Stores the given package record.
     ///
     /// The `missing` set is the set of content digests that are currently
@@ -204,25 +558,36 @@ pub trait DataStore: Send + Sync {
         registry_index: RegistryIndex,
     ) -> Result<(), DataStoreError>;
 
-    /// Determines if the given content digest is missing for the record.
+    /// Sets the present flag for the given record and content digest.
     ///
     /// The record must be in a pending state.
-    async fn is_content_missing(
+    ///
+    /// Returns true if the record has all of its content present as a
+    /// result of this update.
+    ///
+    /// Returns false if the given digest was already marked present.
+    ///
+    /// If `content_size` is provided, it is recorded as the size in bytes
+    /// of the content with the given digest.
+    async fn set_content_present(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
         digest: &AnyHash,
+        content_size: Option<u64>,
     ) -> Result<bool, DataStoreError>;
 
-    /// Sets the present flag for the given record and content digest.
+    /// Marks present content of the given record as validated against its
+    /// digest.
     ///
-    /// The record must be in a pending state.
+    /// The record must be in a pending state, and can only be committed once
+    /// all of its content has been validated.
     ///
-    /// Returns true if the record has all of its content present as a
+    /// Returns true if the record has all of its content validated as a
     /// result of this update.
     ///
-    /// Returns false if the given digest was already marked present.
-    async fn set_content_present(
+    /// Returns `ContentNotPresent` if the content has not been marked present.
+    async fn set_content_validated(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
@@ -235,12 +600,105 @@ pub trait DataStore: Send + Sync {
         checkpoint_id: &AnyHash,
         ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
     ) -> Result<(), DataStoreError>;
+}
+
+/// The methods of a [`DataStore`] that do not mutate the store.
+///
+/// Handlers that only read from the store can take a `&dyn ReadOnlyDataStore`
+/// so that mutating the store is a compile-time error:
+///
+/// ```compile_fail,E0599
+/// use warg_protocol::registry::{LogId, RecordId};
+/// use warg_server::datastore::ReadOnlyDataStore;
+///
+/// async fn handler(store: &dyn ReadOnlyDataStore, log_id: &LogId, record_id: &RecordId) {
+///     store.reject_operator_record(log_id, record_id, "rejected").await.unwrap();
+/// }
+/// ```
+///
+/// Every [`DataStore`] is a `ReadOnlyDataStore`.
+#[axum::async_trait]
+pub trait ReadOnlyDataStore: Send + Sync {
+    /// Gets a stream of all checkpoints.
+    ///
+    /// This is an expensive operation and should only be performed on startup.
+    async fn get_all_checkpoints(
+        &self,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+        DataStoreError,
+    >;
+
+    /// Gets a stream of all validated records.
+    ///
+    /// This is an expensive operation and should only be performed on startup.
+    async fn get_all_validated_records(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>;
+
+    /// Gets a stream of validated records starting at the given registry index.
+    ///
+    /// This allows a consumer of the full stream to resume where it left off.
+    async fn get_all_validated_records_from(
+        &self,
+        start: RegistryIndex,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>;
+
+    /// Looks up the log_id and record_id from the registry log index.  
+    async fn get_log_leafs_with_registry_index(
+        &self,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<LogLeaf>, DataStoreError>;
+
+    /// Determines if the given content digest is missing for the record.
+    ///
+    /// The record must be in a pending state.
+    async fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError>;
+
+    /// Gets the total size in bytes of the content of the given record.
+    ///
+    /// Only content with a recorded size contributes to the total.
+    ///
+    /// Operator records have no content, so their size is always zero.
+    async fn get_content_size(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<u64, DataStoreError>;
+
+    /// Gets the current length of the registry log.
+    ///
+    /// This is the number of committed log leafs, which is also the
+    /// registry index of the next leaf to be committed.
+    ///
+    /// Unlike the latest checkpoint, this includes leafs that have not
+    /// yet been checkpointed.
+    async fn current_registry_length(&self) -> Result<RegistryLen, DataStoreError>;
+
+    /// Gets totals describing the contents of the store.
+    ///
+    /// The totals are computed from a single consistent view of the store.
+    async fn get_store_stats(&self) -> Result<StoreStats, DataStoreError>;
 
     /// Gets the latest checkpoint.
     async fn get_latest_checkpoint(
         &self,
     ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError>;
 
+    /// Gets the latest checkpoint along with the id of the key that signed it.
+    async fn get_latest_checkpoint_with_key(
+        &self,
+    ) -> Result<(SerdeEnvelope<TimestampedCheckpoint>, KeyID), DataStoreError> {
+        let checkpoint = self.get_latest_checkpoint().await?;
+        let key_id = checkpoint.key_id().clone();
+        Ok((checkpoint, key_id))
+    }
+
     /// Get checkpoint by log length.
     async fn get_checkpoint(
         &self,
@@ -278,6 +736,19 @@ pub trait DataStore: Send + Sync {
         limit: u16,
     ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError>;
 
+    /// Gets the package records for the given registry log length,
+    /// starting after the given registry index.
+    ///
+    /// If no record in the log has the given registry index, the records
+    /// start with the first record with a greater registry index.
+    async fn get_package_records_since_index(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since_index: Option<RegistryIndex>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError>;
+
     /// Gets an operator record.
     async fn get_operator_record(
         &self,
@@ -292,6 +763,11 @@ pub trait DataStore: Send + Sync {
         record_id: &RecordId,
     ) -> Result<Record<package::PackageRecord>, DataStoreError>;
 
+    /// Gets the id of the last committed record of a log.
+    ///
+    /// Returns `None` if the log has no committed records.
+    async fn get_head_record_id(&self, log_id: &LogId) -> Result<Option<RecordId>, DataStoreError>;
+
     /// Verifies the signature of a package record.
     ///
     /// This is different from `validate_package_record` in that
@@ -304,6 +780,18 @@ pub trait DataStore: Send + Sync {
         record: &ProtoEnvelope<package::PackageRecord>,
     ) -> Result<(), DataStoreError>;
 
+    /// Verifies the signature of an operator record.
+    ///
+    /// This is different from `validate_operator_record` in that
+    /// only the signature on the envelope is verified.
+    ///
+    /// It does not attempt to validate the record itself.
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError>;
+
     /// Verifies the package name is unique in a case insensitive way and that the
     /// package namespace is defined for this registry and is not imported
     /// from another registry.
@@ -320,6 +808,16 @@ pub trait DataStore: Send + Sync {
         ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
     ) -> Result<(), DataStoreError>;
 
+    /// Verifies the signatures of multiple TimestampedCheckpoints.
+    ///
+    /// Returns `CheckpointSignatureFailed` with the index of the first
+    /// checkpoint that fails verification.
+    async fn verify_timestamped_checkpoints(
+        &self,
+        operator_log_id: &LogId,
+        checkpoints: &[SerdeEnvelope<TimestampedCheckpoint>],
+    ) -> Result<(), DataStoreError>;
+
     // Returns a list of package names, for debugging only.
     #[cfg(feature = "debug")]
     #[doc(hidden)]
@@ -0,0 +1,2 @@
+ALTER TABLE contents
+  ADD COLUMN size BIGINT;
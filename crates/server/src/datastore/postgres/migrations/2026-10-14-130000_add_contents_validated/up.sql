@@ -0,0 +1,4 @@
+-- Content present before this migration was validated when it was uploaded
+ALTER TABLE contents
+  ADD COLUMN validated BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE contents SET validated = NOT missing;
@@ -2,7 +2,10 @@ use self::models::{
     CheckpointData, NewCheckpoint, NewContent, NewLog, NewRecord, ParsedText, RecordContent,
     RecordStatus, TextRef,
 };
-use super::{DataStore, DataStoreError, Record};
+use super::{
+    DataStore, DataStoreError, DataStoreMetrics, LogKind, NoopDataStoreMetrics, ReadOnlyDataStore,
+    Record, StoreStats,
+};
 use anyhow::{anyhow, Result};
 use diesel::sql_types::{Nullable, Text};
 use diesel::{prelude::*, result::DatabaseErrorKind};
@@ -18,10 +21,10 @@ use diesel_migrations::{
 use futures::{Stream, StreamExt};
 use indexmap::{IndexMap, IndexSet};
 use secrecy::{ExposeSecret, SecretString};
-use std::pin::Pin;
-use warg_crypto::{hash::AnyHash, Decode, Encode, Signable};
+use std::{pin::Pin, sync::Arc};
+use warg_crypto::{hash::AnyHash, Decode, Signable};
 use warg_protocol::{
-    operator,
+    operator::{self, OperatorEntry},
     package::{self, PackageEntry},
     registry::{
         Checkpoint, LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen,
@@ -40,6 +43,7 @@ async fn get_records<R: Decode>(
     log_id: i32,
     registry_log_length: RegistryLen,
     since: Option<&RecordId>,
+    since_index: Option<RegistryIndex>,
     limit: i64,
 ) -> Result<Vec<PublishedProtoEnvelope<R>>, DataStoreError> {
     schema::checkpoints::table
@@ -78,6 +82,10 @@ async fn get_records<R: Decode>(
         query = query.filter(schema::records::id.gt(record_id));
     }
 
+    if let Some(since_index) = since_index {
+        query = query.filter(schema::records::registry_log_index.gt(since_index as i64));
+    }
+
     query
         .load::<(ParsedText<AnyHash>, Vec<u8>, Option<i64>)>(conn)
         .await?
@@ -116,13 +124,19 @@ where
             // Unfortunately, this cannot be done with an ON CONFLICT DO NOTHING clause as
             // data cannot be returned; so just do a query for the log id and insert if it doesn't exist.
             let log_id = match schema::logs::table
-                .select(schema::logs::id)
+                .select((schema::logs::id, schema::logs::name))
                 .filter(schema::logs::log_id.eq(TextRef(log_id)))
-                .first::<i32>(conn)
+                .first::<(i32, Option<String>)>(conn)
                 .await
                 .optional()?
             {
-                Some(id) => id,
+                Some((_, Some(existing))) if name.is_some_and(|name| name != existing) => {
+                    return Err(DataStoreError::PackageNameMismatch {
+                        existing,
+                        got: name.unwrap().to_string(),
+                    });
+                }
+                Some((id, _)) => id,
                 None => diesel::insert_into(schema::logs::table)
                     .values(NewLog {
                         log_id: TextRef(log_id),
@@ -166,6 +180,8 @@ where
                                 record_id,
                                 digest: TextRef(s),
                                 missing: missing.contains(s),
+                                // Content already present was validated when it was uploaded
+                                validated: !missing.contains(s),
                             })
                             .collect::<Vec<_>>(),
                     )
@@ -207,12 +223,15 @@ async fn reject_record(
     Ok(())
 }
 
+/// Commits a pending record.
+///
+/// Returns `false` if the record was already committed at the given registry index.
 async fn commit_record<V>(
     conn: &mut AsyncPgConnection,
     log_id: i32,
     record_id: &RecordId,
     registry_index: RegistryIndex,
-) -> Result<(), DataStoreError>
+) -> Result<bool, DataStoreError>
 where
     V: Validator + 'static,
     <V as Validator>::Error: ToString + Send + Sync,
@@ -221,6 +240,42 @@ where
     let registry_index: i64 = registry_index.try_into().unwrap();
     conn.transaction::<_, DataStoreError, _>(|conn| {
         async move {
+            // Allow retried commits of an already committed record
+            let committed = schema::records::table
+                .select(schema::records::registry_log_index)
+                .filter(
+                    schema::records::record_id
+                        .eq(TextRef(record_id))
+                        .and(schema::records::log_id.eq(log_id))
+                        .and(schema::records::status.eq(RecordStatus::Validated)),
+                )
+                .first::<Option<i64>>(conn)
+                .await
+                .optional()?
+                .flatten();
+            match committed {
+                Some(at) if at == registry_index => return Ok(false),
+                Some(at) => {
+                    return Err(DataStoreError::RecordAlreadyCommitted {
+                        at: at as RegistryIndex,
+                    })
+                }
+                None => {}
+            }
+
+            // Ensure the record is committed at the next registry index
+            let expected = schema::records::table
+                .filter(schema::records::registry_log_index.is_not_null())
+                .count()
+                .get_result::<i64>(conn)
+                .await?;
+            if registry_index != expected {
+                return Err(DataStoreError::NonContiguousRegistryIndex {
+                    expected: expected as RegistryIndex,
+                    got: registry_index as RegistryIndex,
+                });
+            }
+
             // Get the record content and validator
             let (id, content, validator) = schema::records::table
                 .inner_join(schema::logs::table)
@@ -241,6 +296,34 @@ where
                 .optional()?
                 .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
 
+            // Ensure all of the record's contents are present and validated
+            if let Some(digest) = schema::contents::table
+                .select(schema::contents::digest)
+                .filter(
+                    schema::contents::record_id
+                        .eq(id)
+                        .and(schema::contents::missing.eq(true)),
+                )
+                .first::<ParsedText<AnyHash>>(conn)
+                .await
+                .optional()?
+            {
+                return Err(DataStoreError::ContentNotPresent(digest.0));
+            }
+            if let Some(digest) = schema::contents::table
+                .select(schema::contents::digest)
+                .filter(
+                    schema::contents::record_id
+                        .eq(id)
+                        .and(schema::contents::validated.eq(false)),
+                )
+                .first::<ParsedText<AnyHash>>(conn)
+                .await
+                .optional()?
+            {
+                return Err(DataStoreError::ContentNotValidated(digest.0));
+            }
+
             let record = ProtoEnvelope::<V::Record>::from_protobuf(&content).map_err(|e| {
                 DataStoreError::InvalidRecordContents {
                     record_id: record_id.clone(),
@@ -268,7 +351,7 @@ where
                 .execute(conn)
                 .await?;
 
-            Ok(())
+            Ok(true)
         }
         .scope_boxed()
     })
@@ -357,13 +440,41 @@ const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/datastore/postgres
 pub struct PostgresDataStore {
     url: SecretString,
     pool: Pool<AsyncPgConnection>,
+    metrics: Arc<dyn DataStoreMetrics>,
+    on_commit_hook: Option<Arc<dyn Fn(RegistryIndex) + Send + Sync>>,
 }
 
 impl PostgresDataStore {
     pub fn new(url: SecretString) -> Result<Self> {
         let config = AsyncDieselConnectionManager::new(url.expose_secret());
         let pool = Pool::builder(config).build()?;
-        Ok(Self { url, pool })
+        Ok(Self {
+            url,
+            pool,
+            metrics: Arc::new(NoopDataStoreMetrics),
+            on_commit_hook: None,
+        })
+    }
+
+    /// Sets the metrics hooks notified of record operations.
+    pub fn with_metrics(mut self, metrics: Arc<dyn DataStoreMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Sets a hook invoked with the registry index of each successfully
+    /// committed record.
+    ///
+    /// The hook is called after the database connection has been released.
+    pub fn with_on_commit_hook(mut self, hook: Arc<dyn Fn(RegistryIndex) + Send + Sync>) -> Self {
+        self.on_commit_hook = Some(hook);
+        self
+    }
+
+    fn notify_commit(&self, registry_index: RegistryIndex) {
+        if let Some(hook) = &self.on_commit_hook {
+            hook(registry_index);
+        }
     }
 
     pub async fn run_pending_migrations(&self) -> Result<()> {
@@ -392,7 +503,7 @@ impl PostgresDataStore {
 }
 
 #[axum::async_trait]
-impl DataStore for PostgresDataStore {
+impl ReadOnlyDataStore for PostgresDataStore {
     async fn get_all_checkpoints(
         &self,
     ) -> Result<
@@ -450,6 +561,35 @@ impl DataStore for PostgresDataStore {
         ))
     }
 
+    async fn get_all_validated_records_from(
+        &self,
+        start: RegistryIndex,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        // The returned future will keep the connection from the pool until dropped
+        let mut conn = self.pool.get().await?;
+
+        Ok(Box::pin(
+            schema::records::table
+                .inner_join(schema::logs::table)
+                .select((schema::logs::log_id, schema::records::record_id))
+                .filter(
+                    schema::records::status
+                        .eq(RecordStatus::Validated)
+                        .and(schema::records::registry_log_index.ge(start as i64)),
+                )
+                .order(schema::records::registry_log_index.asc())
+                .load_stream::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(&mut conn)
+                .await?
+                .map(|r| {
+                    r.map_err(Into::into).map(|(log_id, record_id)| LogLeaf {
+                        log_id: log_id.0.into(),
+                        record_id: record_id.0.into(),
+                    })
+                }),
+        ))
+    }
+
     async fn get_log_leafs_starting_with_registry_index(
         &self,
         starting_index: RegistryIndex,
@@ -557,30 +697,35 @@ impl DataStore for PostgresDataStore {
         Ok(map)
     }
 
-    async fn store_operator_record(
+    async fn is_content_missing(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-        record: &ProtoEnvelope<operator::OperatorRecord>,
-    ) -> Result<(), DataStoreError> {
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
         let mut conn = self.pool.get().await?;
-        insert_record::<operator::LogState>(
-            conn.as_mut(),
-            log_id,
-            None,
-            record_id,
-            record,
-            &Default::default(),
-        )
-        .await
+        schema::contents::table
+            .inner_join(schema::records::table)
+            .inner_join(schema::logs::table.on(schema::logs::id.eq(schema::records::log_id)))
+            .select(schema::contents::missing)
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Pending)
+                    .and(schema::logs::log_id.eq(TextRef(log_id)))
+                    .and(schema::records::record_id.eq(TextRef(record_id)))
+                    .and(schema::contents::digest.eq(TextRef(digest))),
+            )
+            .first::<bool>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))
     }
 
-    async fn reject_operator_record(
+    async fn get_content_size(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-        reason: &str,
-    ) -> Result<(), DataStoreError> {
+    ) -> Result<u64, DataStoreError> {
         let mut conn = self.pool.get().await?;
         let log_id = schema::logs::table
             .select(schema::logs::id)
@@ -590,61 +735,187 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        reject_record(conn.as_mut(), log_id, record_id, reason).await
+        let id = schema::records::table
+            .select(schema::records::id)
+            .filter(
+                schema::records::record_id
+                    .eq(TextRef(record_id))
+                    .and(schema::records::log_id.eq(log_id)),
+            )
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        // Operator records have no content rows, so their size is zero
+        Ok(schema::contents::table
+            .select(schema::contents::size)
+            .filter(schema::contents::record_id.eq(id))
+            .load::<Option<i64>>(conn.as_mut())
+            .await?
+            .into_iter()
+            .flatten()
+            .map(|size| size as u64)
+            .sum())
     }
 
-    async fn commit_operator_record(
+    async fn current_registry_length(&self) -> Result<RegistryLen, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let len = schema::records::table
+            .filter(schema::records::registry_log_index.is_not_null())
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await?;
+
+        Ok(len as RegistryLen)
+    }
+
+    async fn get_store_stats(&self) -> Result<StoreStats, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        // Use a repeatable read transaction so the counts are consistent
+        conn.build_transaction()
+            .read_only()
+            .repeatable_read()
+            .run::<_, DataStoreError, _>(|conn| {
+                async move {
+                    // Operator logs are the only unnamed logs
+                    let operator_logs = schema::logs::table
+                        .filter(schema::logs::name.is_null())
+                        .count()
+                        .get_result::<i64>(conn)
+                        .await?;
+                    let package_logs = schema::logs::table
+                        .filter(schema::logs::name.is_not_null())
+                        .count()
+                        .get_result::<i64>(conn)
+                        .await?;
+
+                    let mut records = [0; 3];
+                    for (count, status) in records.iter_mut().zip([
+                        RecordStatus::Validated,
+                        RecordStatus::Pending,
+                        RecordStatus::Rejected,
+                    ]) {
+                        *count = schema::records::table
+                            .filter(schema::records::status.eq(status))
+                            .count()
+                            .get_result::<i64>(conn)
+                            .await?;
+                    }
+
+                    let checkpoints = schema::checkpoints::table
+                        .count()
+                        .get_result::<i64>(conn)
+                        .await?;
+
+                    let [validated_records, pending_records, rejected_records] = records;
+                    Ok(StoreStats {
+                        operator_logs: operator_logs as u64,
+                        package_logs: package_logs as u64,
+                        validated_records: validated_records as u64,
+                        pending_records: pending_records as u64,
+                        rejected_records: rejected_records as u64,
+                        checkpoints: checkpoints as u64,
+                    })
+                }
+                .scope_boxed()
+            })
+            .await
+    }
+
+    async fn get_latest_checkpoint(
         &self,
-        log_id: &LogId,
-        record_id: &RecordId,
-        registry_index: RegistryIndex,
-    ) -> Result<(), DataStoreError> {
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
         let mut conn = self.pool.get().await?;
-        let log_id = schema::logs::table
-            .select(schema::logs::id)
-            .filter(schema::logs::log_id.eq(TextRef(log_id)))
-            .first::<i32>(conn.as_mut())
+
+        let checkpoint = schema::checkpoints::table
+            .order_by(schema::checkpoints::id.desc())
+            .first::<CheckpointData>(&mut conn)
             .await
             .optional()?
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+            .ok_or(DataStoreError::NoCheckpoint)?;
 
-        match commit_record::<operator::LogState>(conn.as_mut(), log_id, record_id, registry_index)
+        let log_length = checkpoint.log_length.try_into().unwrap();
+
+        Ok(SerdeEnvelope::from_parts_unchecked(
+            TimestampedCheckpoint {
+                checkpoint: Checkpoint {
+                    log_root: checkpoint.log_root.0,
+                    log_length,
+                    map_root: checkpoint.map_root.0,
+                },
+                timestamp: checkpoint.timestamp.try_into().unwrap(),
+            },
+            checkpoint.key_id.0,
+            checkpoint.signature.0,
+        ))
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let checkpoint = schema::checkpoints::table
+            .filter(schema::checkpoints::log_length.eq(log_length as i64))
+            .first::<CheckpointData>(&mut conn)
             .await
-        {
-            Ok(()) => Ok(()),
-            Err(e) => {
-                reject_record(conn.as_mut(), log_id, record_id, &e.to_string()).await?;
-                Err(e)
-            }
-        }
+            .optional()?
+            .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+
+        Ok(SerdeEnvelope::from_parts_unchecked(
+            TimestampedCheckpoint {
+                checkpoint: Checkpoint {
+                    log_root: checkpoint.log_root.0,
+                    log_length,
+                    map_root: checkpoint.map_root.0,
+                },
+                timestamp: checkpoint.timestamp.try_into().unwrap(),
+            },
+            checkpoint.key_id.0,
+            checkpoint.signature.0,
+        ))
     }
 
-    async fn store_package_record(
+    #[tracing::instrument(skip_all, fields(%log_id))]
+    async fn get_operator_records(
         &self,
         log_id: &LogId,
-        package_name: &PackageName,
-        record_id: &RecordId,
-        record: &ProtoEnvelope<package::PackageRecord>,
-        missing: &IndexSet<&AnyHash>,
-    ) -> Result<(), DataStoreError> {
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
         let mut conn = self.pool.get().await?;
-        insert_record::<package::LogState>(
-            conn.as_mut(),
+        let log_id = schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        get_records(
+            &mut conn,
             log_id,
-            Some(package_name.as_ref()),
-            record_id,
-            record,
-            missing,
+            registry_log_length,
+            since,
+            None,
+            limit as i64,
         )
         .await
     }
 
-    async fn reject_package_record(
+    #[tracing::instrument(skip_all, fields(%log_id))]
+    async fn get_package_records(
         &self,
         log_id: &LogId,
-        record_id: &RecordId,
-        reason: &str,
-    ) -> Result<(), DataStoreError> {
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
         let mut conn = self.pool.get().await?;
         let log_id = schema::logs::table
             .select(schema::logs::id)
@@ -654,15 +925,25 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        reject_record(conn.as_mut(), log_id, record_id, reason).await
+        get_records(
+            &mut conn,
+            log_id,
+            registry_log_length,
+            since,
+            None,
+            limit as i64,
+        )
+        .await
     }
 
-    async fn commit_package_record(
+    #[tracing::instrument(skip_all, fields(%log_id))]
+    async fn get_package_records_since_index(
         &self,
         log_id: &LogId,
-        record_id: &RecordId,
-        registry_index: RegistryIndex,
-    ) -> Result<(), DataStoreError> {
+        registry_log_length: RegistryLen,
+        since_index: Option<RegistryIndex>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
         let mut conn = self.pool.get().await?;
         let log_id = schema::logs::table
             .select(schema::logs::id)
@@ -672,216 +953,38 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        match commit_record::<package::LogState>(conn.as_mut(), log_id, record_id, registry_index)
-            .await
-        {
-            Ok(()) => Ok(()),
-            Err(e) => {
-                reject_record(conn.as_mut(), log_id, record_id, &e.to_string()).await?;
-                Err(e)
-            }
-        }
+        get_records(
+            &mut conn,
+            log_id,
+            registry_log_length,
+            None,
+            since_index,
+            limit as i64,
+        )
+        .await
     }
 
-    async fn is_content_missing(
+    async fn get_operator_record(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-        digest: &AnyHash,
-    ) -> Result<bool, DataStoreError> {
+    ) -> Result<Record<operator::OperatorRecord>, DataStoreError> {
         let mut conn = self.pool.get().await?;
-        schema::contents::table
-            .inner_join(schema::records::table)
-            .inner_join(schema::logs::table.on(schema::logs::id.eq(schema::records::log_id)))
-            .select(schema::contents::missing)
-            .filter(
-                schema::records::status
-                    .eq(RecordStatus::Pending)
-                    .and(schema::logs::log_id.eq(TextRef(log_id)))
-                    .and(schema::records::record_id.eq(TextRef(record_id)))
-                    .and(schema::contents::digest.eq(TextRef(digest))),
-            )
-            .first::<bool>(conn.as_mut())
-            .await
-            .optional()?
-            .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))
+        get_record::<operator::LogState>(conn.as_mut(), log_id, record_id).await
     }
 
-    async fn set_content_present(
+    async fn get_package_record(
         &self,
         log_id: &LogId,
         record_id: &RecordId,
-        digest: &AnyHash,
-    ) -> Result<bool, DataStoreError> {
-        let mut conn = self.pool.get().await?;
-        conn.transaction::<_, DataStoreError, _>(|conn| {
-            // Diesel currently doesn't support joins for updates
-            // See: https://github.com/diesel-rs/diesel/issues/1478
-            // So we select the record id first and then update the content
-            async move {
-                let record_id = schema::records::table
-                    .inner_join(schema::logs::table)
-                    .select(schema::records::id)
-                    .filter(
-                        schema::records::status
-                            .eq(RecordStatus::Pending)
-                            .and(schema::logs::log_id.eq(TextRef(log_id)))
-                            .and(schema::records::record_id.eq(TextRef(record_id))),
-                    )
-                    .first::<i32>(conn.as_mut())
-                    .await
-                    .optional()?
-                    .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
-
-                // If the row was already updated, return false since this update
-                // didn't change anything
-                if diesel::update(schema::contents::table)
-                    .filter(
-                        schema::contents::record_id
-                            .eq(record_id)
-                            .and(schema::contents::digest.eq(TextRef(digest))),
-                    )
-                    .set(schema::contents::missing.eq(false))
-                    .execute(conn.as_mut())
-                    .await?
-                    == 0
-                {
-                    return Ok(false);
-                }
-
-                // Finally, check if all contents are present; if so, return true
-                // to indicate that this record is ready to be processed
-                let missing = schema::contents::table
-                    .select(schema::contents::id)
-                    .filter(
-                        schema::contents::record_id
-                            .eq(record_id)
-                            .and(schema::contents::missing.eq(true)),
-                    )
-                    .first::<i32>(conn.as_mut())
-                    .await
-                    .optional()?;
-
-                Ok(missing.is_none())
-            }
-            .scope_boxed()
-        })
-        .await
-    }
-
-    async fn store_checkpoint(
-        &self,
-        checkpoint_id: &AnyHash,
-        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
-    ) -> Result<(), DataStoreError> {
-        let mut conn = self.pool.get().await?;
-
-        conn.transaction::<_, DataStoreError, _>(|conn| {
-            async move {
-                let TimestampedCheckpoint {
-                    checkpoint:
-                        Checkpoint {
-                            log_root,
-                            log_length,
-                            map_root,
-                        },
-                    timestamp,
-                } = ts_checkpoint.as_ref();
-
-                // Replacing any existing checkpoint with the same checkpoint_id
-                diesel::delete(
-                    schema::checkpoints::dsl::checkpoints
-                        .filter(schema::checkpoints::checkpoint_id.eq(TextRef(checkpoint_id))),
-                )
-                .execute(conn)
-                .await?;
-
-                // Insert the checkpoint
-                diesel::insert_into(schema::checkpoints::table)
-                    .values(NewCheckpoint {
-                        checkpoint_id: TextRef(checkpoint_id),
-                        log_root: TextRef(log_root),
-                        map_root: TextRef(map_root),
-                        log_length: *log_length as i64,
-                        key_id: TextRef(ts_checkpoint.key_id()),
-                        signature: TextRef(ts_checkpoint.signature()),
-                        timestamp: (*timestamp).try_into().unwrap(),
-                    })
-                    .returning(schema::checkpoints::id)
-                    .get_result::<i32>(conn)
-                    .await?;
-
-                Ok(())
-            }
-            .scope_boxed()
-        })
-        .await?;
-
-        Ok(())
-    }
-
-    async fn get_latest_checkpoint(
-        &self,
-    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
-        let mut conn = self.pool.get().await?;
-
-        let checkpoint = schema::checkpoints::table
-            .order_by(schema::checkpoints::id.desc())
-            .first::<CheckpointData>(&mut conn)
-            .await?;
-
-        let log_length = checkpoint.log_length.try_into().unwrap();
-
-        Ok(SerdeEnvelope::from_parts_unchecked(
-            TimestampedCheckpoint {
-                checkpoint: Checkpoint {
-                    log_root: checkpoint.log_root.0,
-                    log_length,
-                    map_root: checkpoint.map_root.0,
-                },
-                timestamp: checkpoint.timestamp.try_into().unwrap(),
-            },
-            checkpoint.key_id.0,
-            checkpoint.signature.0,
-        ))
-    }
-
-    async fn get_checkpoint(
-        &self,
-        log_length: RegistryLen,
-    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+    ) -> Result<Record<package::PackageRecord>, DataStoreError> {
         let mut conn = self.pool.get().await?;
-
-        let checkpoint = schema::checkpoints::table
-            .filter(schema::checkpoints::log_length.eq(log_length as i64))
-            .first::<CheckpointData>(&mut conn)
-            .await
-            .optional()?
-            .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
-
-        Ok(SerdeEnvelope::from_parts_unchecked(
-            TimestampedCheckpoint {
-                checkpoint: Checkpoint {
-                    log_root: checkpoint.log_root.0,
-                    log_length,
-                    map_root: checkpoint.map_root.0,
-                },
-                timestamp: checkpoint.timestamp.try_into().unwrap(),
-            },
-            checkpoint.key_id.0,
-            checkpoint.signature.0,
-        ))
+        get_record::<package::LogState>(conn.as_mut(), log_id, record_id).await
     }
 
-    async fn get_operator_records(
-        &self,
-        log_id: &LogId,
-        registry_log_length: RegistryLen,
-        since: Option<&RecordId>,
-        limit: u16,
-    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+    async fn get_head_record_id(&self, log_id: &LogId) -> Result<Option<RecordId>, DataStoreError> {
         let mut conn = self.pool.get().await?;
-        let log_id = schema::logs::table
+        let id = schema::logs::table
             .select(schema::logs::id)
             .filter(schema::logs::log_id.eq(TextRef(log_id)))
             .first::<i32>(conn.as_mut())
@@ -889,57 +992,61 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        get_records(&mut conn, log_id, registry_log_length, since, limit as i64).await
+        Ok(schema::records::table
+            .select(schema::records::record_id)
+            .filter(
+                schema::records::log_id
+                    .eq(id)
+                    .and(schema::records::status.eq(RecordStatus::Validated)),
+            )
+            .order_by(schema::records::registry_log_index.desc())
+            .first::<ParsedText<AnyHash>>(conn.as_mut())
+            .await
+            .optional()?
+            .map(|record_id| record_id.0.into()))
     }
 
-    async fn get_package_records(
+    async fn verify_package_record_signature(
         &self,
         log_id: &LogId,
-        registry_log_length: RegistryLen,
-        since: Option<&RecordId>,
-        limit: u16,
-    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), DataStoreError> {
         let mut conn = self.pool.get().await?;
-        let log_id = schema::logs::table
-            .select(schema::logs::id)
+
+        let validator = schema::logs::table
+            .select(schema::logs::validator)
             .filter(schema::logs::log_id.eq(TextRef(log_id)))
-            .first::<i32>(conn.as_mut())
+            .first::<Json<package::LogState>>(&mut conn)
             .await
-            .optional()?
-            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
-
-        get_records(&mut conn, log_id, registry_log_length, since, limit as i64).await
-    }
+            .optional()?;
 
-    async fn get_operator_record(
-        &self,
-        log_id: &LogId,
-        record_id: &RecordId,
-    ) -> Result<Record<operator::OperatorRecord>, DataStoreError> {
-        let mut conn = self.pool.get().await?;
-        get_record::<operator::LogState>(conn.as_mut(), log_id, record_id).await
-    }
+        #[allow(clippy::get_first)] // Vec::first() conflicts with diesel's RunQueryDsl
+        let key = match validator
+            .as_ref()
+            .and_then(|v| v.public_key(record.key_id()))
+        {
+            Some(key) => key,
+            None => match record.as_ref().entries.get(0) {
+                Some(PackageEntry::Init { key, .. }) => key,
+                _ => return Err(DataStoreError::UnknownKey(record.key_id().clone())),
+            },
+        };
 
-    async fn get_package_record(
-        &self,
-        log_id: &LogId,
-        record_id: &RecordId,
-    ) -> Result<Record<package::PackageRecord>, DataStoreError> {
-        let mut conn = self.pool.get().await?;
-        get_record::<package::LogState>(conn.as_mut(), log_id, record_id).await
+        package::PackageRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
     }
 
-    async fn verify_package_record_signature(
+    async fn verify_operator_record_signature(
         &self,
         log_id: &LogId,
-        record: &ProtoEnvelope<package::PackageRecord>,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
     ) -> Result<(), DataStoreError> {
         let mut conn = self.pool.get().await?;
 
         let validator = schema::logs::table
             .select(schema::logs::validator)
             .filter(schema::logs::log_id.eq(TextRef(log_id)))
-            .first::<Json<package::LogState>>(&mut conn)
+            .first::<Json<operator::LogState>>(&mut conn)
             .await
             .optional()?;
 
@@ -950,12 +1057,12 @@ impl DataStore for PostgresDataStore {
         {
             Some(key) => key,
             None => match record.as_ref().entries.get(0) {
-                Some(PackageEntry::Init { key, .. }) => key,
+                Some(OperatorEntry::Init { key, .. }) => key,
                 _ => return Err(DataStoreError::UnknownKey(record.key_id().clone())),
             },
         };
 
-        package::PackageRecord::verify(key, record.content_bytes(), record.signature())
+        operator::OperatorRecord::verify(key, record.content_bytes(), record.signature())
             .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
     }
 
@@ -985,8 +1092,9 @@ impl DataStore for PostgresDataStore {
                 }
             },
             None => {
-                return Err(DataStoreError::PackageNamespaceNotDefined(
-                    package_name.namespace().to_string(),
+                return Err(super::namespace_not_defined(
+                    &validator,
+                    package_name.namespace(),
                 ))
             }
         }
@@ -1009,37 +1117,497 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
 
-        TimestampedCheckpoint::verify(
-            validator
-                .public_key(ts_checkpoint.key_id())
-                .ok_or(DataStoreError::UnknownKey(ts_checkpoint.key_id().clone()))?,
-            &ts_checkpoint.as_ref().encode(),
-            ts_checkpoint.signature(),
-        )
-        .or(Err(DataStoreError::SignatureVerificationFailed(
-            ts_checkpoint.signature().clone(),
-        )))?;
-
-        if !validator.key_has_permission_to_sign_checkpoints(ts_checkpoint.key_id()) {
-            return Err(DataStoreError::KeyUnauthorized(
-                ts_checkpoint.key_id().clone(),
-            ));
-        }
-
-        Ok(())
+        super::verify_checkpoint_signature(&validator, ts_checkpoint)
     }
 
-    #[cfg(feature = "debug")]
-    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
+    async fn verify_timestamped_checkpoints(
+        &self,
+        operator_log_id: &LogId,
+        checkpoints: &[SerdeEnvelope<TimestampedCheckpoint>],
+    ) -> Result<(), DataStoreError> {
         let mut conn = self.pool.get().await?;
-        let names = schema::logs::table
-            .select(schema::logs::name)
-            .load::<Option<String>>(&mut conn)
-            .await?
-            .into_iter()
+
+        let validator = schema::logs::table
+            .select(schema::logs::validator)
+            .filter(schema::logs::log_id.eq(TextRef(operator_log_id)))
+            .first::<Json<operator::LogState>>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        super::verify_checkpoint_signatures(&validator, checkpoints)
+    }
+
+    #[cfg(feature = "debug")]
+    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
+        let mut conn = self.pool.get().await?;
+        let names = schema::logs::table
+            .select(schema::logs::name)
+            .load::<Option<String>>(&mut conn)
+            .await?
+            .into_iter()
             .flatten()
             .filter_map(|name| name.parse().ok())
             .collect();
         Ok(names)
     }
 }
+
+#[axum::async_trait]
+impl DataStore for PostgresDataStore {
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn store_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        insert_record::<operator::LogState>(
+            conn.as_mut(),
+            log_id,
+            None,
+            record_id,
+            record,
+            &Default::default(),
+        )
+        .await?;
+        self.metrics.on_store(LogKind::Operator);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn reject_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let log_id = schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        reject_record(conn.as_mut(), log_id, record_id, reason).await?;
+        self.metrics.on_reject(LogKind::Operator);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id, %registry_index))]
+    async fn commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let log_id = schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        match commit_record::<operator::LogState>(conn.as_mut(), log_id, record_id, registry_index)
+            .await
+        {
+            Ok(true) => {
+                self.metrics.on_commit(LogKind::Operator);
+                drop(conn);
+                self.notify_commit(registry_index);
+                Ok(())
+            }
+            Ok(false) => Ok(()),
+            Err(
+                e @ (DataStoreError::NonContiguousRegistryIndex { .. }
+                | DataStoreError::RecordAlreadyCommitted { .. }),
+            ) => Err(e),
+            Err(e) => {
+                reject_record(conn.as_mut(), log_id, record_id, &e.to_string()).await?;
+                self.metrics.on_reject(LogKind::Operator);
+                Err(e)
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id, %registry_index))]
+    async fn store_and_commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        // Store and commit in one transaction so that a commit at the wrong
+        // index doesn't leave the record pending; a record that fails
+        // validation is still stored as rejected
+        let mut conn = self.pool.get().await?;
+        let committed = conn
+            .as_mut()
+            .transaction::<_, DataStoreError, _>(|conn| {
+                async move {
+                    insert_record::<operator::LogState>(
+                        conn,
+                        log_id,
+                        None,
+                        record_id,
+                        record,
+                        &Default::default(),
+                    )
+                    .await?;
+
+                    let log_id = schema::logs::table
+                        .select(schema::logs::id)
+                        .filter(schema::logs::log_id.eq(TextRef(log_id)))
+                        .first::<i32>(conn)
+                        .await?;
+
+                    match commit_record::<operator::LogState>(
+                        conn,
+                        log_id,
+                        record_id,
+                        registry_index,
+                    )
+                    .await
+                    {
+                        Ok(committed) => Ok(Ok(committed)),
+                        Err(
+                            e @ (DataStoreError::NonContiguousRegistryIndex { .. }
+                            | DataStoreError::RecordAlreadyCommitted { .. }),
+                        ) => Err(e),
+                        Err(e) => {
+                            reject_record(conn, log_id, record_id, &e.to_string()).await?;
+                            Ok(Err(e))
+                        }
+                    }
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        self.metrics.on_store(LogKind::Operator);
+        match committed {
+            Ok(true) => {
+                self.metrics.on_commit(LogKind::Operator);
+                drop(conn);
+                self.notify_commit(registry_index);
+                Ok(())
+            }
+            Ok(false) => Ok(()),
+            Err(e) => {
+                self.metrics.on_reject(LogKind::Operator);
+                Err(e)
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn store_package_record(
+        &self,
+        log_id: &LogId,
+        package_name: &PackageName,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        missing: &IndexSet<&AnyHash>,
+    ) -> Result<(), DataStoreError> {
+        super::ensure_missing_in_contents(record, missing)?;
+
+        let mut conn = self.pool.get().await?;
+        insert_record::<package::LogState>(
+            conn.as_mut(),
+            log_id,
+            Some(package_name.as_ref()),
+            record_id,
+            record,
+            missing,
+        )
+        .await?;
+        self.metrics.on_store(LogKind::Package);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id))]
+    async fn reject_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let log_id = schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        reject_record(conn.as_mut(), log_id, record_id, reason).await?;
+        self.metrics.on_reject(LogKind::Package);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(%log_id, %record_id, %registry_index))]
+    async fn commit_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let log_id = schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        match commit_record::<package::LogState>(conn.as_mut(), log_id, record_id, registry_index)
+            .await
+        {
+            Ok(true) => {
+                self.metrics.on_commit(LogKind::Package);
+                drop(conn);
+                self.notify_commit(registry_index);
+                Ok(())
+            }
+            Ok(false) => Ok(()),
+            Err(
+                e @ (DataStoreError::NonContiguousRegistryIndex { .. }
+                | DataStoreError::RecordAlreadyCommitted { .. }),
+            ) => Err(e),
+            Err(e) => {
+                reject_record(conn.as_mut(), log_id, record_id, &e.to_string()).await?;
+                self.metrics.on_reject(LogKind::Package);
+                Err(e)
+            }
+        }
+    }
+
+    async fn set_content_present(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        content_size: Option<u64>,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            // Diesel currently doesn't support joins for updates
+            // See: https://github.com/diesel-rs/diesel/issues/1478
+            // So we select the record id first and then update the content
+            async move {
+                let record_id = schema::records::table
+                    .inner_join(schema::logs::table)
+                    .select(schema::records::id)
+                    .filter(
+                        schema::records::status
+                            .eq(RecordStatus::Pending)
+                            .and(schema::logs::log_id.eq(TextRef(log_id)))
+                            .and(schema::records::record_id.eq(TextRef(record_id))),
+                    )
+                    .first::<i32>(conn.as_mut())
+                    .await
+                    .optional()?
+                    .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
+
+                if let Some(size) = content_size {
+                    let expected = schema::contents::table
+                        .select(schema::contents::size)
+                        .filter(
+                            schema::contents::digest
+                                .eq(TextRef(digest))
+                                .and(schema::contents::size.is_not_null())
+                                .and(schema::contents::size.ne(size as i64)),
+                        )
+                        .first::<Option<i64>>(conn.as_mut())
+                        .await
+                        .optional()?
+                        .flatten();
+                    if let Some(expected) = expected {
+                        return Err(DataStoreError::ContentSizeMismatch {
+                            digest: digest.clone(),
+                            expected: expected as u64,
+                            got: size,
+                        });
+                    }
+
+                    diesel::update(schema::contents::table)
+                        .filter(
+                            schema::contents::record_id
+                                .eq(record_id)
+                                .and(schema::contents::digest.eq(TextRef(digest))),
+                        )
+                        .set(schema::contents::size.eq(Some(size as i64)))
+                        .execute(conn.as_mut())
+                        .await?;
+                }
+
+                // If the row was already updated, return false since this update
+                // didn't change anything
+                if diesel::update(schema::contents::table)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(record_id)
+                            .and(schema::contents::digest.eq(TextRef(digest))),
+                    )
+                    .set(schema::contents::missing.eq(false))
+                    .execute(conn.as_mut())
+                    .await?
+                    == 0
+                {
+                    return Ok(false);
+                }
+
+                // Finally, check if all contents are present; if so, return true
+                // to indicate that this record is ready to be processed
+                let missing = schema::contents::table
+                    .select(schema::contents::id)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(record_id)
+                            .and(schema::contents::missing.eq(true)),
+                    )
+                    .first::<i32>(conn.as_mut())
+                    .await
+                    .optional()?;
+
+                Ok(missing.is_none())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    async fn set_content_validated(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                let record_id = schema::records::table
+                    .inner_join(schema::logs::table)
+                    .select(schema::records::id)
+                    .filter(
+                        schema::records::status
+                            .eq(RecordStatus::Pending)
+                            .and(schema::logs::log_id.eq(TextRef(log_id)))
+                            .and(schema::records::record_id.eq(TextRef(record_id))),
+                    )
+                    .first::<i32>(conn.as_mut())
+                    .await
+                    .optional()?
+                    .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
+
+                let missing = schema::contents::table
+                    .select(schema::contents::missing)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(record_id)
+                            .and(schema::contents::digest.eq(TextRef(digest))),
+                    )
+                    .first::<bool>(conn.as_mut())
+                    .await
+                    .optional()?;
+                if missing == Some(true) {
+                    return Err(DataStoreError::ContentNotPresent(digest.clone()));
+                }
+
+                // If the content was already validated, return false since this
+                // update didn't change anything
+                if diesel::update(schema::contents::table)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(record_id)
+                            .and(schema::contents::digest.eq(TextRef(digest)))
+                            .and(schema::contents::validated.eq(false)),
+                    )
+                    .set(schema::contents::validated.eq(true))
+                    .execute(conn.as_mut())
+                    .await?
+                    == 0
+                {
+                    return Ok(false);
+                }
+
+                // Finally, check if all contents are validated; if so, return true
+                // to indicate that this record is ready to be processed
+                let unvalidated = schema::contents::table
+                    .select(schema::contents::id)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(record_id)
+                            .and(schema::contents::validated.eq(false)),
+                    )
+                    .first::<i32>(conn.as_mut())
+                    .await
+                    .optional()?;
+
+                Ok(unvalidated.is_none())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip_all, fields(%checkpoint_id))]
+    async fn store_checkpoint(
+        &self,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                let TimestampedCheckpoint {
+                    checkpoint:
+                        Checkpoint {
+                            log_root,
+                            log_length,
+                            map_root,
+                        },
+                    timestamp,
+                } = ts_checkpoint.as_ref();
+
+                // Replacing any existing checkpoint with the same checkpoint_id
+                diesel::delete(
+                    schema::checkpoints::dsl::checkpoints
+                        .filter(schema::checkpoints::checkpoint_id.eq(TextRef(checkpoint_id))),
+                )
+                .execute(conn)
+                .await?;
+
+                // Insert the checkpoint
+                diesel::insert_into(schema::checkpoints::table)
+                    .values(NewCheckpoint {
+                        checkpoint_id: TextRef(checkpoint_id),
+                        log_root: TextRef(log_root),
+                        map_root: TextRef(map_root),
+                        log_length: *log_length as i64,
+                        key_id: TextRef(ts_checkpoint.key_id()),
+                        signature: TextRef(ts_checkpoint.signature()),
+                        timestamp: (*timestamp).try_into().unwrap(),
+                    })
+                    .returning(schema::checkpoints::id)
+                    .get_result::<i32>(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+        Ok(())
+    }
+}
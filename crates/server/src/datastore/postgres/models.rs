@@ -121,4 +121,5 @@ pub struct NewContent<'a> {
     pub record_id: i32,
     pub digest: TextRef<'a, AnyHash>,
     pub missing: bool,
+    pub validated: bool,
 }
@@ -29,6 +29,8 @@ diesel::table! {
         missing -> Bool,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        size -> Nullable<Int8>,
+        validated -> Bool,
     }
 }
 
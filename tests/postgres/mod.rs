@@ -2,10 +2,15 @@
 
 use super::{support::*, *};
 use anyhow::{Context, Result};
+use indexmap::IndexSet;
 use testresult::TestResult;
 use warg_client::api;
-use warg_protocol::registry::RegistryLen;
-use warg_server::datastore::{DataStore, PostgresDataStore};
+use warg_crypto::hash::{AnyHash, Hash};
+use warg_protocol::{
+    operator::{OperatorEntry, OperatorRecord, OPERATOR_RECORD_VERSION},
+    registry::{RecordId, RegistryLen},
+};
+use warg_server::datastore::{DataStore, DataStoreError, PostgresDataStore};
 
 fn data_store() -> Result<Box<dyn DataStore>> {
     Ok(Box::new(PostgresDataStore::new(
@@ -121,6 +126,110 @@ async fn it_works_with_postgres() -> TestResult {
     .await?;
 
     test_custom_content_url(&config).await?;
+    test_commit_requires_validated_content().await?;
+    test_store_and_commit_non_contiguous().await?;
+
+    Ok(())
+}
+
+/// Ensures a record can't be committed until its content is present and validated.
+async fn test_commit_requires_validated_content() -> Result<()> {
+    let store = data_store()?;
+    let name = PackageName::new("test:missing-content")?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let content: AnyHash = Hash::<Sha256>::of("missing content").into();
+
+    let signing_key = test_signing_key();
+    let record = ProtoEnvelope::signed_contents(
+        &signing_key,
+        PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: signing_key.public_key(),
+                },
+                PackageEntry::Release {
+                    version: "0.1.0".parse()?,
+                    content: content.clone(),
+                },
+            ],
+        },
+    )?;
+    let record_id = RecordId::package_record::<Sha256>(&record);
+
+    store
+        .store_package_record(
+            &log_id,
+            &name,
+            &record_id,
+            &record,
+            &IndexSet::from([&content]),
+        )
+        .await?;
+
+    let index = store.current_registry_length().await?;
+    match store
+        .commit_package_record(&log_id, &record_id, index)
+        .await
+    {
+        Err(DataStoreError::ContentNotPresent(digest)) => assert_eq!(digest, content),
+        res => panic!("expected missing content, got {res:?}"),
+    }
+
+    store
+        .set_content_present(&log_id, &record_id, &content, None)
+        .await?;
+    match store
+        .commit_package_record(&log_id, &record_id, index)
+        .await
+    {
+        Err(DataStoreError::ContentNotValidated(digest)) => assert_eq!(digest, content),
+        res => panic!("expected unvalidated content, got {res:?}"),
+    }
+
+    store
+        .reject_package_record(&log_id, &record_id, "content was never validated")
+        .await?;
+
+    Ok(())
+}
+
+/// Ensures a failed store-and-commit of an operator record doesn't leave it pending.
+async fn test_store_and_commit_non_contiguous() -> Result<()> {
+    let store = data_store()?;
+    let log_id = LogId::operator_log::<Sha256>();
+    let record = ProtoEnvelope::signed_contents(
+        &test_signing_key(),
+        OperatorRecord {
+            prev: None,
+            version: OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![OperatorEntry::DefineNamespace {
+                namespace: "non-contiguous".to_string(),
+            }],
+        },
+    )?;
+    let record_id = RecordId::operator_record::<Sha256>(&record);
+
+    let expected = store.current_registry_length().await?;
+    match store
+        .store_and_commit_operator_record(&log_id, &record_id, &record, expected + 1)
+        .await
+    {
+        Err(DataStoreError::NonContiguousRegistryIndex { expected: e, got }) => {
+            assert_eq!(e, expected);
+            assert_eq!(got, expected + 1);
+        }
+        res => panic!("expected a non-contiguous index, got {res:?}"),
+    }
+
+    assert!(matches!(
+        store.get_operator_record(&log_id, &record_id).await,
+        Err(DataStoreError::RecordNotFound(_))
+    ));
 
     Ok(())
 }